@@ -32,6 +32,19 @@ impl GeisterAction {
             actor,
         }
     }
+
+    /// 指定した行動で👻が移動した後の位置`position`を`Some(position)`として返す．
+    /// フィールドから上がる行動や，移動後の位置が負になる行動に対しては`None`を返す．
+    pub fn position_after_movement(&self) -> Option<TableIndex> {
+        match self.geister_movement {
+            GeisterMovement::Direction(d) => {
+                let p = self.target_geister_position.try_cast::<isize>().ok()?;
+                let position_after_movement = p + d;
+                position_after_movement.try_cast().ok()
+            }
+            GeisterMovement::Clear => None,
+        }
+    }
 }
 
 impl Action for GeisterAction {
@@ -0,0 +1,190 @@
+use crate::{BitboardState, GeisterRule, GeisterRules, GeisterState, InitialPlacement};
+use minimax_strategy::{actors, Actor, Rule};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+/// ベンチマークで使う乱数の種．
+/// どの方式も同じ順序で行動を列挙し，同じ乱数で行動を選ぶため，各方式で同じ対局を再現する．
+const BENCHMARK_SEED: u64 = 0;
+
+/// 行動を判定する局面を集めるプレイアウトの回数．
+const CHECKED_PLAYOUT_COUNT: usize = 100;
+
+/// 行動の列挙方式ごとに，ランダムプレイアウトの速度を測って表示する．
+/// 続けて，行動がひとつでもとれるかの判定方式ごとに，判定の速度を測って表示する．
+/// 各方式が同じ対局を再現したことを確かめられるよう，終局した状態のZobristハッシュを合わせたチェックサムも表示する．
+/// # Params
+/// 1. `rules` プレイアウトに使うルール．手数の上限がなければ`GeisterRules::DEFAULT_MAX_PLY`手で打ち切る．
+/// 1. `playout_count` プレイアウトの回数．
+/// 1. `placement` 各プレイアウトにおける各プレイヤーの初期配置の決め方．
+pub fn run_benchmark(rules: GeisterRules, playout_count: usize, placement: &dyn InitialPlacement) {
+    let rules = match rules.max_ply() {
+        Some(_) => rules,
+        None => rules.with_max_ply(Some(GeisterRules::DEFAULT_MAX_PLY)),
+    };
+    // すべての方式で同じ初期状態からプレイアウトを行う
+    let mut rng = StdRng::seed_from_u64(BENCHMARK_SEED);
+    let initial_states = (0..playout_count)
+        .map(|_| {
+            let first_setup = placement.select_setup(&rules, Actor::First, &mut rng);
+            let second_setup = placement.select_setup(&rules, Actor::Second, &mut rng);
+            GeisterState::create_initial_state(rules, &first_setup, &second_setup)
+        })
+        .collect::<Result<Vec<_>, _>>();
+    let initial_states = match initial_states {
+        Ok(initial_states) => initial_states,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    println!("Random playouts");
+    report("Vec", measure(&initial_states, playout_with_vec));
+    report("Iterator", measure(&initial_states, playout_with_iterator));
+    if initial_states
+        .iter()
+        .all(|state| BitboardState::from_state(state).is_some())
+    {
+        report("Bitboard", measure(&initial_states, playout_with_bitboard));
+    } else {
+        println!("Bitboard is not available for this field size");
+    }
+
+    println!("Legal action checks");
+    let states = visited_states(&initial_states[..initial_states.len().min(CHECKED_PLAYOUT_COUNT)]);
+    report_check("Vec", &states, |state, actor| {
+        !GeisterRule::iterate_available_actions(state, actor).is_empty()
+    });
+    report_check("Count", &states, |state, actor| {
+        GeisterRule::count_legal_actions(state, actor) > 0
+    });
+    report_check("HasAny", &states, GeisterRule::has_any_legal_action);
+}
+
+/// 各初期状態からプレイアウトを行い，かかった時間と終局した状態を返す．
+fn measure<F>(initial_states: &[GeisterState], playout: F) -> (Duration, Vec<GeisterState>)
+where
+    F: Fn(&GeisterState, &mut StdRng) -> GeisterState,
+{
+    let mut rng = StdRng::seed_from_u64(BENCHMARK_SEED);
+    let start = Instant::now();
+    let final_states = initial_states
+        .iter()
+        .map(|state| playout(state, &mut rng))
+        .collect();
+    (start.elapsed(), final_states)
+}
+
+/// 測定結果を表示する．
+fn report(name: &str, (elapsed, final_states): (Duration, Vec<GeisterState>)) {
+    let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+    let ply_count = final_states.iter().map(GeisterState::ply).sum::<usize>();
+    let checksum = final_states.iter().fold(0u64, |checksum, state| {
+        checksum.rotate_left(1) ^ state.zobrist_hash()
+    });
+    println!(
+        "{:>10}: {} positions in {:.3} s ({:.0} positions/s, checksum {:016x})",
+        name,
+        ply_count,
+        seconds,
+        ply_count as f64 / seconds,
+        checksum
+    );
+}
+
+/// 各状態について，指定した方式で両プレイヤーに行動がひとつでもあるか判定し，その速度を表示する．
+/// 各方式が同じ判定をしたことを確かめられるよう，行動のあった数も表示する．
+fn report_check<F>(name: &str, states: &[GeisterState], has_any_legal_action: F)
+where
+    F: Fn(&GeisterState, Actor) -> bool,
+{
+    let start = Instant::now();
+    let mut available_count = 0;
+    for state in states.iter() {
+        for &actor in actors().iter() {
+            if has_any_legal_action(state, actor) {
+                available_count += 1;
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+    let check_count = states.len() * actors().len();
+    println!(
+        "{:>10}: {} checks in {:.3} s ({:.0} checks/s, {} with actions)",
+        name,
+        check_count,
+        seconds,
+        check_count as f64 / seconds,
+        available_count
+    );
+}
+
+/// 各初期状態から行動をベクタに列挙する方式と同じプレイアウトを行い，現れた状態をすべて返す．
+fn visited_states(initial_states: &[GeisterState]) -> Vec<GeisterState> {
+    let mut rng = StdRng::seed_from_u64(BENCHMARK_SEED);
+    let mut states = vec![];
+    for initial_state in initial_states.iter() {
+        let mut state = initial_state.clone();
+        states.push(state.clone());
+        while !GeisterRule::is_game_over(&state) {
+            let actions = GeisterRule::iterate_available_actions(&state, state.current_actor());
+            if actions.is_empty() {
+                break;
+            }
+            let action = actions[rng.gen_range(0, actions.len())];
+            GeisterRule::apply(&mut state, &action);
+            states.push(state.clone());
+        }
+    }
+    states
+}
+
+/// 行動をベクタに列挙してランダムに選ぶプレイアウトを行い，終局した状態を返す．
+fn playout_with_vec(initial_state: &GeisterState, rng: &mut StdRng) -> GeisterState {
+    let mut state = initial_state.clone();
+    while !GeisterRule::is_game_over(&state) {
+        let actions = GeisterRule::iterate_available_actions(&state, state.current_actor());
+        if actions.is_empty() {
+            break;
+        }
+        let action = actions[rng.gen_range(0, actions.len())];
+        GeisterRule::apply(&mut state, &action);
+    }
+    state
+}
+
+/// 行動をイテレータで列挙してランダムに選ぶプレイアウトを行い，終局した状態を返す．
+/// ベクタを使う方式と同じ行動を選ぶため，行動を数えてから選んだ番号の行動まで列挙し直す．
+/// 行動がなければ最初の行動を探した時点で打ち切り，あれば1手あたり2回列挙するが，ヒープは使わない．
+fn playout_with_iterator(initial_state: &GeisterState, rng: &mut StdRng) -> GeisterState {
+    let mut state = initial_state.clone();
+    while !GeisterRule::is_game_over(&state) {
+        let actor = state.current_actor();
+        if !GeisterRule::has_any_legal_action(&state, actor) {
+            break;
+        }
+        let count = GeisterRule::count_legal_actions(&state, actor);
+        let action = GeisterRule::legal_actions(&state, actor)
+            .nth(rng.gen_range(0, count))
+            .expect("Action must exist");
+        GeisterRule::apply(&mut state, &action);
+    }
+    state
+}
+
+/// ビットボードでランダムプレイアウトを行い，終局した状態を返す．
+fn playout_with_bitboard(initial_state: &GeisterState, rng: &mut StdRng) -> GeisterState {
+    let mut state = BitboardState::from_state(initial_state).expect("Field must be small enough");
+    while state.outcome().is_none() {
+        let actions = state.available_actions(state.current_actor());
+        if actions.is_empty() {
+            break;
+        }
+        let action = actions[rng.gen_range(0, actions.len())];
+        state.apply(&action);
+    }
+    state.to_state()
+}
@@ -0,0 +1,356 @@
+use crate::{
+    geisters, GameOutcome, Geister, GeisterAction, GeisterMovement, GeisterRule, GeisterRules,
+    GeisterState, OwnedGeister, AVAILABLE_ACTIONS,
+};
+use data_structure::{Pair, Table, TableIndex};
+use minimax_strategy::{actors, Actor};
+use std::collections::HashMap;
+
+/// フィールドの各マスを64ビット整数の各ビットに対応させたゲームGeisterの状態．
+/// 位置`(x, y)`のマスは`y * 幅 + x`番目のビットに対応する．
+/// 大量の状態を生成・複製する探索やプレイアウトのための表現で，マスが64個以下のフィールドにだけ使える．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitboardState {
+    /// このゲームのルール．
+    rules: GeisterRules,
+    /// フィールドの幅．
+    width: usize,
+    /// フィールド上のすべてのマスを表すマスク．
+    field_mask: u64,
+    /// フィールドの左端の列を表すマスク．
+    first_column_mask: u64,
+    /// フィールドの右端の列を表すマスク．
+    last_column_mask: u64,
+    /// 各プレイヤーの脱出マスを表すマスク．
+    clearable_masks: [u64; 2],
+    /// 所有者・種類ごとの👻の位置．`geister_masks[所有者][種類]`として参照する．
+    geister_masks: [[u64; 2]; 2],
+    /// 所有者・種類ごとの，フィールドから取り除かれた👻の数．
+    killed_geister_counts: [[usize; 2]; 2],
+    /// フィールドから上がった👻の所有者．
+    actor_of_cleared_geister: Option<Actor>,
+    /// ゲーム開始から進んだ手数．
+    ply: usize,
+}
+
+impl BitboardState {
+    /// 指定した状態をビットボードで表す．
+    /// フィールドのマスが64個を超える場合は`None`を返す．
+    pub fn from_state(state: &GeisterState) -> Option<Self> {
+        let rules = *state.rules();
+        let size = rules.field_size();
+        let square_count = size.x * size.y;
+        if square_count > 64 {
+            return None;
+        }
+
+        let field_mask = if square_count == 64 {
+            u64::MAX
+        } else {
+            (1 << square_count) - 1
+        };
+        let column_mask = |x: usize| (0..size.y).fold(0u64, |mask, y| mask | 1 << (y * size.x + x));
+        let position_mask = |positions: &[TableIndex]| {
+            positions
+                .iter()
+                .fold(0u64, |mask, p| mask | 1 << (p.y * size.x + p.x))
+        };
+
+        let mut bitboard = Self {
+            rules,
+            width: size.x,
+            field_mask,
+            first_column_mask: column_mask(0),
+            last_column_mask: column_mask(size.x - 1),
+            clearable_masks: [
+                position_mask(&rules.clearable_positions_of(Actor::First)),
+                position_mask(&rules.clearable_positions_of(Actor::Second)),
+            ],
+            geister_masks: [[0; 2]; 2],
+            killed_geister_counts: [[0; 2]; 2],
+            actor_of_cleared_geister: state.actor_of_cleared_geister(),
+            ply: state.ply(),
+        };
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                if let Some(owned_geister) = state.geister_at(TableIndex::new(x, y)) {
+                    bitboard.geister_masks[actor_index(owned_geister.owner)]
+                        [geister_index(owned_geister.geister)] |= 1 << (y * size.x + x);
+                }
+            }
+        }
+        for &actor in actors().iter() {
+            for &geister in geisters().iter() {
+                bitboard.killed_geister_counts[actor_index(actor)][geister_index(geister)] =
+                    state.killed_geister_count(OwnedGeister::new(geister, actor));
+            }
+        }
+
+        Some(bitboard)
+    }
+
+    /// ビットボードを通常の状態に戻す．
+    pub fn to_state(self) -> GeisterState {
+        let size = self.rules.field_size();
+        let mut lattices = Table::from_fill(None, size);
+        let mut killed_geister_counts = HashMap::new();
+        for &actor in actors().iter() {
+            for &geister in geisters().iter() {
+                let owned_geister = OwnedGeister::new(geister, actor);
+                let mut mask = self.geister_masks[actor_index(actor)][geister_index(geister)];
+                while mask != 0 {
+                    lattices[self.position_of(mask.trailing_zeros() as usize)] =
+                        Some(owned_geister);
+                    mask &= mask - 1;
+                }
+                killed_geister_counts.insert(
+                    owned_geister,
+                    self.killed_geister_counts[actor_index(actor)][geister_index(geister)],
+                );
+            }
+        }
+
+        GeisterState::from_parts(
+            self.rules,
+            lattices,
+            killed_geister_counts,
+            self.actor_of_cleared_geister,
+            self.ply,
+        )
+    }
+
+    /// 手番のプレイヤーを返す．
+    pub fn current_actor(&self) -> Actor {
+        GeisterRule::actor_on_ply(self.ply)
+    }
+
+    /// ゲームの結果`outcome`を`Some(outcome)`として返す．
+    /// ゲームが終了していない場合は`None`を返す．
+    /// `GeisterRule::outcome_of()`と同じ判定をする．
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        GeisterRule::outcome_from_parts(
+            &self.rules,
+            |owned_geister| {
+                self.killed_geister_counts[actor_index(owned_geister.owner)]
+                    [geister_index(owned_geister.geister)]
+            },
+            self.actor_of_cleared_geister,
+            self.ply,
+        )
+    }
+
+    /// 指定したプレイヤーがとれる行動を列挙する．
+    /// `GeisterRule::iterate_available_actions()`と同じく，👻の位置の行優先の順に，
+    /// 各👻については`AVAILABLE_ACTIONS`の順に列挙する．
+    pub fn available_actions(&self, actor: Actor) -> Vec<GeisterAction> {
+        let source_masks = self.source_masks_of(actor);
+        // 行動の順序を揃えるため，いずれかの動作をとれる👻について，位置の順に動作を並べる
+        let mut actions = vec![];
+        let mut source_mask = source_masks.iter().fold(0, |mask, &source| mask | source);
+        while source_mask != 0 {
+            let source = source_mask & source_mask.wrapping_neg();
+            let position = self.position_of(source.trailing_zeros() as usize);
+            for (&movement, &mask) in AVAILABLE_ACTIONS.iter().zip(source_masks.iter()) {
+                if mask & source != 0 {
+                    actions.push(GeisterAction::new(position, movement, actor));
+                }
+            }
+            source_mask &= source_mask - 1;
+        }
+        actions
+    }
+
+    /// `AVAILABLE_ACTIONS`の各動作について，その動作をとれる指定したプレイヤーの👻の位置を表すマスクを返す．
+    /// 👻ごとではなく，フィールド全体をずらしてまとめて求める．
+    fn source_masks_of(&self, actor: Actor) -> [u64; AVAILABLE_ACTIONS.len()] {
+        let own_mask = self.own_mask_of(actor);
+        AVAILABLE_ACTIONS.map(|movement| match movement {
+            // 移動先がフィールド内で，自分の👻がいないマスなら移動できる．
+            // 移動先をまとめて求めてから，逆向きにずらして移動元に戻す
+            GeisterMovement::Direction(d) => {
+                let destination_mask = self.shift(own_mask, d) & !own_mask;
+                self.shift(destination_mask, Pair::new(-d.x, -d.y))
+            }
+            // 脱出マスにいる善良な👻は上がれる
+            GeisterMovement::Clear => {
+                self.geister_masks[actor_index(actor)][geister_index(Geister::Holy)]
+                    & self.clearable_masks[actor_index(actor)]
+            }
+        })
+    }
+
+    /// 指定した行動をその場で適用する．
+    /// 行動は`available_actions()`で列挙されたものでなければならない．
+    pub fn apply(&mut self, action: &GeisterAction) {
+        let actor = actor_index(action.actor);
+        let source = self.bit_of(action.target_geister_position);
+        let geister = if self.geister_masks[actor][geister_index(Geister::Holy)] & source != 0 {
+            geister_index(Geister::Holy)
+        } else {
+            geister_index(Geister::Evil)
+        };
+        debug_assert_ne!(self.geister_masks[actor][geister] & source, 0);
+        self.geister_masks[actor][geister] &= !source;
+
+        match action.geister_movement {
+            GeisterMovement::Direction(d) => {
+                let destination = self.shift(source, d);
+                debug_assert_ne!(destination, 0);
+                // 移動先に相手の👻がいれば取り除く
+                let opponent = actor_index(action.actor.opponent());
+                for &killed_geister in geisters().iter() {
+                    let killed_geister = geister_index(killed_geister);
+                    if self.geister_masks[opponent][killed_geister] & destination != 0 {
+                        self.geister_masks[opponent][killed_geister] &= !destination;
+                        self.killed_geister_counts[opponent][killed_geister] += 1;
+                    }
+                }
+                self.geister_masks[actor][geister] |= destination;
+            }
+            GeisterMovement::Clear => {
+                self.actor_of_cleared_geister = Some(action.actor);
+            }
+        }
+        self.ply += 1;
+    }
+
+    /// 指定したプレイヤーの👻がいるマスを表すマスクを返す．
+    fn own_mask_of(&self, actor: Actor) -> u64 {
+        let masks = self.geister_masks[actor_index(actor)];
+        masks[0] | masks[1]
+    }
+
+    /// マスクが表す各マスを，指定した方向に1マスずらす．
+    /// フィールドからはみ出たマスは取り除かれる．
+    fn shift(&self, mask: u64, direction: Pair<isize>) -> u64 {
+        match (direction.x, direction.y) {
+            (1, 0) => (mask & !self.last_column_mask) << 1,
+            (-1, 0) => (mask & !self.first_column_mask) >> 1,
+            (0, 1) => (mask << self.width) & self.field_mask,
+            (0, -1) => mask >> self.width,
+            _ => unreachable!("Geister moves only to adjacent squares"),
+        }
+    }
+
+    /// 指定した位置に対応するビットを返す．
+    fn bit_of(&self, position: TableIndex) -> u64 {
+        1 << (position.y * self.width + position.x)
+    }
+
+    /// 指定したビットに対応する位置を返す．
+    fn position_of(&self, bit_index: usize) -> TableIndex {
+        TableIndex::new(bit_index % self.width, bit_index / self.width)
+    }
+}
+
+/// 配列の添字として使う，プレイヤーの番号を返す．
+fn actor_index(actor: Actor) -> usize {
+    match actor {
+        Actor::First => 0,
+        Actor::Second => 1,
+    }
+}
+
+/// 配列の添字として使う，👻の種類の番号を返す．
+fn geister_index(geister: Geister) -> usize {
+    match geister {
+        Geister::Holy => 0,
+        Geister::Evil => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InitialPlacement, RandomPlacement};
+    use data_structure::TableSize;
+    use minimax_strategy::Rule;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    /// ルールごとに試す対局の数．
+    const GAME_COUNT: usize = 20;
+
+    /// ランダムな対局の各局面で，指定した確認をする．
+    fn for_each_position_of_random_games<F>(seed: u64, mut check: F)
+    where
+        F: FnMut(&GeisterState, &BitboardState, &mut StdRng),
+    {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let rules_list = vec![
+            GeisterRules::standard(),
+            GeisterRules::with_field_size(TableSize::new(4, 4)).unwrap(),
+            GeisterRules::with_field_size(TableSize::new(8, 8)).unwrap(),
+        ];
+        for rules in rules_list {
+            let rules = rules.with_max_ply(Some(200));
+            for _ in 0..GAME_COUNT {
+                let first_setup = RandomPlacement.select_setup(&rules, Actor::First, &mut rng);
+                let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, &mut rng);
+                let mut state =
+                    GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+                loop {
+                    let bitboard = BitboardState::from_state(&state).unwrap();
+                    check(&state, &bitboard, &mut rng);
+                    if GeisterRule::is_game_over(&state) {
+                        break;
+                    }
+                    let actions =
+                        GeisterRule::iterate_available_actions(&state, state.current_actor());
+                    match actions.choose(&mut rng) {
+                        Some(action) => {
+                            GeisterRule::apply(&mut state, action);
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_state_and_to_state_round_trip() {
+        for_each_position_of_random_games(1, |state, bitboard, _rng| {
+            let rebuilt = bitboard.to_state();
+            // 状態の等価性は手数を比べないため，手数は別に確かめる
+            assert_eq!(*state, rebuilt);
+            assert_eq!(state.ply(), rebuilt.ply());
+            assert_eq!(state.current_actor(), bitboard.current_actor());
+            assert_eq!(GeisterRule::outcome_of(state), bitboard.outcome());
+        });
+    }
+
+    #[test]
+    fn available_actions_match_rule() {
+        for_each_position_of_random_games(2, |state, bitboard, _rng| {
+            for &actor in actors().iter() {
+                // 集合として等しいだけでなく，列挙の順序も等しい
+                assert_eq!(
+                    GeisterRule::iterate_available_actions(state, actor),
+                    bitboard.available_actions(actor)
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn apply_matches_rule() {
+        for_each_position_of_random_games(3, |state, bitboard, rng| {
+            if GeisterRule::is_game_over(state) {
+                return;
+            }
+            let actions = GeisterRule::iterate_available_actions(state, state.current_actor());
+            if let Some(action) = actions.choose(rng) {
+                let mut bitboard = *bitboard;
+                bitboard.apply(action);
+                let translated = GeisterRule::translate_state(state, action);
+                let applied = bitboard.to_state();
+                assert_eq!(translated, applied);
+                assert_eq!(translated.ply(), applied.ply());
+                assert_eq!(translated.zobrist_hash(), applied.zobrist_hash());
+            }
+        });
+    }
+}
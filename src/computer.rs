@@ -0,0 +1,217 @@
+use crate::{GeisterAction, GeisterEvaluator, GeisterRule, PlayerView};
+use minimax_strategy::{Actor, Evaluator, Rule, Strategy};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+/// αβ法で指定した手数先まで探索し，利得が最大となる行動を選ぶ戦略．
+/// 状態の遷移と終局の判定には`minimax_strategy`の`Rule`を，状態の評価には`Evaluator`を使うため，
+/// すべての情報が見える状態であれば，どのゲームにも使える．
+pub struct MinimaxStrategy<R, E> {
+    /// 探索する手数．
+    depth: usize,
+    /// 探索に使うルールと評価関数．
+    rule_and_evaluator: PhantomData<(R, E)>,
+}
+
+impl<R, E> MinimaxStrategy<R, E>
+where
+    R: Rule,
+    R::ActionIterator: IntoIterator<Item = R::A>,
+    E: Evaluator<R::S>,
+    E::Payoff: Ord + Copy,
+{
+    /// 指定した手数まで探索する戦略を生成する．
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            rule_and_evaluator: PhantomData,
+        }
+    }
+
+    /// 指定したプレイヤーがとれる行動と，その行動をとった場合の利得の組を，
+    /// `Rule::iterate_available_actions()`の順に返す．
+    pub fn evaluate_actions(&self, state: &R::S, actor: Actor) -> Vec<(R::A, E::Payoff)> {
+        R::iterate_available_actions(state, actor)
+            .into_iter()
+            .map(|action| {
+                let next_state = R::translate_state(state, &action);
+                let payoff = Self::alpha_beta(
+                    &next_state,
+                    self.depth.saturating_sub(1),
+                    actor,
+                    actor.opponent(),
+                    None,
+                    None,
+                );
+                (action, payoff)
+            })
+            .collect()
+    }
+
+    /// αβ法により，指定した状態から`depth`手先まで探索した場合の`actor`にとっての利得を返す．
+    /// 手番は`current_actor`から交互に移るものとする．
+    /// `alpha`と`beta`は探索の窓の下限と上限で，`None`は限りがないことを表す．
+    fn alpha_beta(
+        state: &R::S,
+        depth: usize,
+        actor: Actor,
+        current_actor: Actor,
+        mut alpha: Option<E::Payoff>,
+        mut beta: Option<E::Payoff>,
+    ) -> E::Payoff {
+        if depth == 0 || R::is_game_over(state) {
+            return E::evaluate_payoff_for(actor, state);
+        }
+
+        // 自分の手番では利得を最大化し，相手の手番では最小化する
+        let mut best = None;
+        for action in R::iterate_available_actions(state, current_actor) {
+            let next_state = R::translate_state(state, &action);
+            let payoff = Self::alpha_beta(
+                &next_state,
+                depth - 1,
+                actor,
+                current_actor.opponent(),
+                alpha,
+                beta,
+            );
+            if current_actor == actor {
+                best = Some(best.map_or(payoff, |best: E::Payoff| best.max(payoff)));
+                alpha = Some(alpha.map_or(payoff, |alpha: E::Payoff| alpha.max(payoff)));
+            } else {
+                best = Some(best.map_or(payoff, |best: E::Payoff| best.min(payoff)));
+                beta = Some(beta.map_or(payoff, |beta: E::Payoff| beta.min(payoff)));
+            }
+            if let (Some(alpha), Some(beta)) = (alpha, beta) {
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+        // とれる行動がなければ，その状態を評価する
+        best.unwrap_or_else(|| E::evaluate_payoff_for(actor, state))
+    }
+}
+
+impl<R, E> Strategy<R::S, R::A> for MinimaxStrategy<R, E>
+where
+    R: Rule,
+    R::ActionIterator: IntoIterator<Item = R::A>,
+    E: Evaluator<R::S>,
+    E::Payoff: Ord + Copy,
+{
+    fn select_action(&self, state: &R::S, actor: Actor) -> Option<R::A> {
+        self.evaluate_actions(state, actor)
+            .into_iter()
+            .max_by_key(|(_action, payoff)| *payoff)
+            .map(|(action, _payoff)| action)
+    }
+}
+
+/// 相手の👻の種類が分からないまま行動を選ぶコンピュータプレイヤー．
+/// 観測と矛盾しない状態を何通りか仮定し，それぞれの状態で`MinimaxStrategy`が求めた各行動の利得を
+/// 合計して行動を選ぶ．
+pub struct ComputerStrategy {
+    /// 仮定した状態ごとに行動を評価する戦略．
+    search: MinimaxStrategy<GeisterRule, GeisterEvaluator>,
+    /// 仮定する状態の数．
+    sample_count: usize,
+    /// 状態を仮定する際に使う乱数生成器．同じ種からは同じ行動を選ぶ．
+    rng: RefCell<StdRng>,
+}
+
+impl ComputerStrategy {
+    /// 仮定する状態の数の既定値．
+    pub const DEFAULT_SAMPLE_COUNT: usize = 8;
+
+    /// 指定した手数まで探索するコンピュータプレイヤーを生成する．
+    /// # Params
+    /// 1. `depth` 探索する手数．
+    /// 1. `seed` 状態を仮定する際に使う乱数の種．
+    pub fn new(depth: usize, seed: u64) -> Self {
+        Self {
+            search: MinimaxStrategy::new(depth),
+            sample_count: Self::DEFAULT_SAMPLE_COUNT,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl Strategy<PlayerView, GeisterAction> for ComputerStrategy {
+    fn select_action(&self, view: &PlayerView, actor: Actor) -> Option<GeisterAction> {
+        let available_actions = view.available_actions();
+        if available_actions.len() <= 1 {
+            return available_actions.into_iter().next();
+        }
+
+        // 各行動の評価値を，仮定した状態すべてについて合計する．
+        // 自分の👻の種類は仮定によらないため，どの状態でもとれる行動は同じ順に並ぶ
+        let mut rng = self.rng.borrow_mut();
+        let mut total_payoffs = vec![0i64; available_actions.len()];
+        for _ in 0..self.sample_count {
+            let state = view.determinize(&mut *rng);
+            let evaluations = self.search.evaluate_actions(&state, actor);
+            for (total_payoff, (action, payoff)) in total_payoffs.iter_mut().zip(evaluations) {
+                debug_assert!(available_actions.contains(&action));
+                *total_payoff += payoff.value() as i64;
+            }
+        }
+
+        available_actions
+            .into_iter()
+            .zip(total_payoffs)
+            .max_by_key(|&(_action, total_payoff)| total_payoff)
+            .map(|(action, _total_payoff)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeisterMovement, GeisterPayoff, GeisterState};
+    use data_structure::TableIndex;
+
+    /// 次の手で善良な👻を上がらせられる状態．
+    const ESCAPABLE: &str = "Hhhhh1/1eeee1/6/6/1EEEE1/1HHH2 f 0,0,0,0 - 0 6x6:4:4";
+
+    #[test]
+    fn minimax_strategy_takes_winning_action() {
+        let state = ESCAPABLE.parse::<GeisterState>().unwrap();
+        for depth in 1..=3 {
+            let strategy = MinimaxStrategy::<GeisterRule, GeisterEvaluator>::new(depth);
+            let action = strategy.select_action(&state, Actor::First).unwrap();
+            assert_eq!(GeisterMovement::Clear, action.geister_movement);
+        }
+    }
+
+    #[test]
+    fn computer_strategy_takes_winning_action() {
+        let state = ESCAPABLE.parse::<GeisterState>().unwrap();
+        let view = PlayerView::new(&state, Actor::First);
+        let action = ComputerStrategy::new(2, 0)
+            .select_action(&view, Actor::First)
+            .unwrap();
+        assert_eq!(GeisterMovement::Clear, action.geister_movement);
+    }
+
+    #[test]
+    fn minimax_strategy_prevents_opponent_escape() {
+        // 後手の善良な👻が脱出マスにいるため，先手はその👻を取らなければ次の手で負ける
+        let state = "1hhh2/1eeee1/6/6/1EEE1E/1HHHHh f 0,0,0,0 - 0 6x6:4:4"
+            .parse::<GeisterState>()
+            .unwrap();
+        let escape_position = TableIndex::new(5, 5);
+        let strategy = MinimaxStrategy::<GeisterRule, GeisterEvaluator>::new(2);
+        for (action, payoff) in strategy.evaluate_actions(&state, Actor::First) {
+            if action.position_after_movement() == Some(escape_position) {
+                assert!(GeisterPayoff::LOSS < payoff);
+            } else {
+                assert_eq!(GeisterPayoff::LOSS, payoff);
+            }
+        }
+        let action = strategy.select_action(&state, Actor::First).unwrap();
+        assert_eq!(Some(escape_position), action.position_after_movement());
+    }
+}
@@ -1,17 +1,186 @@
-use crate::GeisterState;
+use crate::{Geister, GeisterRule, GeisterState, OwnedGeister};
+use data_structure::TableIndex;
 use minimax_strategy::{Actor, Evaluator};
 
 /// ゲームGeisterのフィールドに対する利得を表す．
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GeisterPayoff(i32);
 
+impl GeisterPayoff {
+    /// 勝利が確定した状態の利得．
+    pub const WIN: GeisterPayoff = GeisterPayoff(i32::MAX);
+    /// 敗北が確定した状態の利得．
+    pub const LOSS: GeisterPayoff = GeisterPayoff(-i32::MAX);
+    /// どちらにも有利でない状態の利得．引き分けもこの利得となる．
+    pub const NEUTRAL: GeisterPayoff = GeisterPayoff(0);
+
+    /// 利得の値を返す．
+    pub fn value(&self) -> i32 {
+        self.0
+    }
+}
+
+/// フィールドに残っている善良な👻ひとつあたりの価値．
+const HOLY_WEIGHT: i32 = 100;
+/// フィールドに残っている邪悪な👻ひとつあたりの価値．
+const EVIL_WEIGHT: i32 = 30;
+/// 善良な👻と脱出マスとの距離1あたりの価値．
+const ESCAPE_DISTANCE_WEIGHT: i32 = 15;
+/// 脱出マスにいる善良な👻の価値．
+const ON_EXIT_WEIGHT: i32 = 400;
+/// 脱出マスの隣にいる善良な👻の価値．
+const NEXT_TO_EXIT_WEIGHT: i32 = 100;
+/// 手番で，かつ脱出マスに善良な👻がいる (次の手で上がれる) 状態の価値．
+const ESCAPE_NEXT_TURN_WEIGHT: i32 = 5000;
+/// とれる行動ひとつあたりの価値．
+const MOBILITY_WEIGHT: i32 = 2;
+
 /// ゲームGeisterのフィールドに対する評価関数を表す．
 pub struct GeisterEvaluator {}
 
 impl Evaluator<GeisterState> for GeisterEvaluator {
     type Payoff = GeisterPayoff;
 
-    fn evaluate_payoff_for(_actor: Actor, _state: &GeisterState) -> Self::Payoff {
-        unimplemented!()
+    fn evaluate_payoff_for(actor: Actor, state: &GeisterState) -> Self::Payoff {
+        // 決着のついた状態は最大・最小の利得とする
+        if let Some(outcome) = GeisterRule::outcome_of(state) {
+            return match outcome.winner() {
+                Some(winner) if winner == actor => GeisterPayoff::WIN,
+                Some(_) => GeisterPayoff::LOSS,
+                None => GeisterPayoff::NEUTRAL,
+            };
+        }
+
+        let score = advantage_of(actor, state) - advantage_of(actor.opponent(), state);
+        // 評価値が決着のついた状態の利得と重ならないようにする
+        GeisterPayoff(score.clamp(GeisterPayoff::LOSS.0 + 1, GeisterPayoff::WIN.0 - 1))
+    }
+}
+
+/// 指定したプレイヤーにとっての状態の良さを，相手の状態を考慮せずに返す．
+fn advantage_of(actor: Actor, state: &GeisterState) -> i32 {
+    let rules = state.rules();
+
+    // 自分の善良な👻が残っているほど良く，邪悪な👻は相手に取らせるほど良い
+    let remaining_count_of = |geister| {
+        (rules.initial_geister_count(geister)
+            - state.killed_geister_count(OwnedGeister::new(geister, actor))) as i32
+    };
+    let material = HOLY_WEIGHT * remaining_count_of(Geister::Holy)
+        - EVIL_WEIGHT * remaining_count_of(Geister::Evil);
+
+    // 善良な👻が脱出マスに近いほど良い
+    let exits = rules.clearable_positions_of(actor);
+    let field_size = rules.field_size();
+    let holy_distances = own_geister_positions(actor, Geister::Holy, state)
+        .map(|position| {
+            exits
+                .iter()
+                .map(|&exit| distance(position, exit))
+                .min()
+                .expect("Exits must exist")
+        })
+        .collect::<Vec<_>>();
+    let escape = match holy_distances.iter().min() {
+        Some(&nearest_distance) => {
+            ESCAPE_DISTANCE_WEIGHT * (field_size.x + field_size.y - nearest_distance) as i32
+        }
+        None => 0,
+    };
+
+    // 脱出マスやその隣にいる善良な👻は，相手の脅威となる
+    let on_exit_count = holy_distances.iter().filter(|&&d| d == 0).count() as i32;
+    let next_to_exit_count = holy_distances.iter().filter(|&&d| d == 1).count() as i32;
+    let threat = if state.current_actor() == actor && on_exit_count > 0 {
+        ESCAPE_NEXT_TURN_WEIGHT
+    } else {
+        ON_EXIT_WEIGHT * on_exit_count + NEXT_TO_EXIT_WEIGHT * next_to_exit_count
+    };
+
+    // とれる行動が多いほど良い
+    let mobility = MOBILITY_WEIGHT * GeisterRule::count_legal_actions(state, actor) as i32;
+
+    material + escape + threat + mobility
+}
+
+/// 指定したプレイヤーが所有する，指定した種類の👻の位置を列挙する．
+fn own_geister_positions<'a>(
+    actor: Actor,
+    geister: Geister,
+    state: &'a GeisterState,
+) -> impl Iterator<Item = TableIndex> + 'a {
+    let owned_geister = OwnedGeister::new(geister, actor);
+    state
+        .lattices()
+        .iter_row()
+        .enumerate()
+        .flat_map(move |(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(move |(_x, &lattice)| lattice == Some(owned_geister))
+                .map(move |(x, _lattice)| TableIndex::new(x, y))
+        })
+}
+
+/// 2つの位置のマンハッタン距離を返す．
+fn distance(a: TableIndex, b: TableIndex) -> usize {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 局面の表記から状態を生成する．
+    fn state_of(s: &str) -> GeisterState {
+        s.parse().unwrap()
+    }
+
+    /// 指定したプレイヤーにとっての状態の評価値を返す．
+    fn payoff_for(actor: Actor, s: &str) -> GeisterPayoff {
+        GeisterEvaluator::evaluate_payoff_for(actor, &state_of(s))
+    }
+
+    #[test]
+    fn finished_games_have_extreme_payoffs() {
+        let escaped = "1hhhh1/1eeee1/6/6/1EEEE1/1HHH2 s 0,0,0,0 f 1 6x6:4:4";
+        assert_eq!(GeisterPayoff::WIN, payoff_for(Actor::First, escaped));
+        assert_eq!(GeisterPayoff::LOSS, payoff_for(Actor::Second, escaped));
+
+        let all_evil_lost = "1hhhh1/6/6/6/1EEEE1/1HHHH1 s 0,0,0,4 - 1 6x6:4:4";
+        assert_eq!(GeisterPayoff::WIN, payoff_for(Actor::Second, all_evil_lost));
+        assert_eq!(GeisterPayoff::LOSS, payoff_for(Actor::First, all_evil_lost));
+
+        let draw = "1hhhh1/1eeee1/6/6/1EEEE1/1HHHH1 f 0,0,0,0 - 10 6x6:4:4:10";
+        assert_eq!(GeisterPayoff::NEUTRAL, payoff_for(Actor::First, draw));
+        assert_eq!(GeisterPayoff::NEUTRAL, payoff_for(Actor::Second, draw));
+    }
+
+    #[test]
+    fn heuristic_payoffs_are_ordered() {
+        let initial = "1hhhh1/1eeee1/6/6/1EEEE1/1HHHH1 f 0,0,0,0 - 0 6x6:4:4";
+        // 相手の善良な👻を取った状態
+        let captured_holy = "1hhh2/1eeee1/6/6/1EEEE1/1HHHH1 f 0,0,1,0 - 0 6x6:4:4";
+        // 自分の善良な👻が脱出マスの隣にいる状態
+        let next_to_exit = "1hhhh1/Heeee1/6/6/1EEEE1/1HHH2 f 0,0,0,0 - 0 6x6:4:4";
+        // 手番で，自分の善良な👻が脱出マスにいる状態
+        let on_exit = "Hhhhh1/1eeee1/6/6/1EEEE1/1HHH2 f 0,0,0,0 - 0 6x6:4:4";
+
+        let payoffs = [initial, captured_holy, next_to_exit, on_exit]
+            .iter()
+            .map(|s| payoff_for(Actor::First, s))
+            .collect::<Vec<_>>();
+        assert!(payoffs[0] < payoffs[1]);
+        assert!(payoffs[0] < payoffs[2]);
+        assert!(payoffs[2] < payoffs[3]);
+        for (s, &payoff) in [initial, captured_holy, next_to_exit, on_exit]
+            .iter()
+            .zip(payoffs.iter())
+        {
+            // 決着のついていない状態は，決着のついた状態の利得と重ならない
+            assert!(GeisterPayoff::LOSS < payoff && payoff < GeisterPayoff::WIN);
+            // 両プレイヤーの評価値は符号が逆になる
+            assert_eq!(-payoff.value(), payoff_for(Actor::Second, s).value());
+        }
     }
 }
@@ -1,132 +1,421 @@
 mod action;
+mod bench;
+mod bitboard;
+mod computer;
 mod evaluation;
+mod notation;
+mod outcome;
+mod placement;
+mod record;
 mod rule;
+mod ruleset;
+mod setup;
 mod state;
+mod view;
+mod zobrist;
 
 use action::*;
+use bench::*;
+use bitboard::*;
+use computer::*;
 use cui_gaming::*;
-use data_structure::{Pair, TableIndex};
-use minimax_strategy::{actors, Actor, Rule, Strategy};
-use rand::seq::SliceRandom;
+use data_structure::{Pair, TableIndex, TableSize};
+use evaluation::*;
+use minimax_strategy::{actors, Actor, Strategy};
+use notation::*;
+use outcome::*;
+use placement::*;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use record::*;
 use rule::*;
+use ruleset::*;
+use setup::*;
 use state::*;
-use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use view::*;
 
 /// ゲームユーザーの意思決定を管理する．
 struct PlayerStrategy {
     /// ユーザーのからのキー入力を監視する．
     keyboard_input: KeyboardInput,
+    /// 人間同士が一つの端末を交代で使う対局かどうか．
+    hot_seat: bool,
 }
 
-impl Strategy<GeisterState, GeisterAction> for PlayerStrategy {
-    fn select_action(&self, state: &GeisterState, actor: Actor) -> Option<GeisterAction> {
-        // まずは実行可能な行動を列挙．このゲームでは必ずひとつ以上の行動がとれるはず．
-        let available_actions = GeisterRule::iterate_available_actions(&state, actor);
-        assert!(available_actions.len() > 0);
-
-        // 自分が所有している👻の位置を求め，右上のものから順に配列に格納していく．
-        let own_geister_positions = {
-            let mut positions = vec![];
-            for (y, row) in state.lattices.iter_row().enumerate() {
-                for (x, &lattice) in row.iter().enumerate() {
-                    if let Some(owned_geister) = lattice {
-                        if owned_geister.owner == actor {
-                            positions.push(TableIndex::new(x, y));
-                        }
-                    }
-                }
-            }
-            positions
-        };
+/// 人間のプレイヤーが手番で選べる操作．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayerCommand {
+    /// 指定した行動をとる．
+    Act(GeisterAction),
+    /// 自分の直前の行動を取り消す．
+    Undo,
+    /// 投了する．
+    Resign,
+}
+
+/// 盤面上のカーソルと，動かす対象として選ばれた👻の移動先．
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BoardCursor {
+    /// カーソルのあるマス．
+    position: TableIndex,
+    /// 動かす対象として選ばれた👻のいるマス．
+    selected: Option<TableIndex>,
+    /// 選ばれた👻がルール上移動できるマス．
+    destinations: Vec<TableIndex>,
+    /// 選ばれた👻がルール上フィールドから上がれるか．
+    can_clear: bool,
+}
+
+impl BoardCursor {
+    /// 指定したマスにカーソルを置き，👻が選ばれていない状態を生成する．
+    fn new(position: TableIndex) -> Self {
+        Self {
+            position,
+            selected: None,
+            destinations: vec![],
+            can_clear: false,
+        }
+    }
+
+    /// 指定したマスの👻を動かす対象として選び，指定した行動のうちその👻の行動の移動先と上がれるかどうかを記録する．
+    fn select(&mut self, position: TableIndex, available_actions: &[GeisterAction]) {
+        let mut actions_of_selected = available_actions
+            .iter()
+            .filter(|action| action.target_geister_position == position);
+        self.selected = Some(position);
+        self.destinations = actions_of_selected
+            .clone()
+            .filter_map(|action| action.position_after_movement())
+            .collect();
+        self.can_clear =
+            actions_of_selected.any(|action| action.geister_movement == GeisterMovement::Clear);
+    }
+
+    /// 👻の選択を解除する．
+    fn deselect(&mut self) {
+        self.selected = None;
+        self.destinations.clear();
+        self.can_clear = false;
+    }
+}
+
+impl PlayerStrategy {
+    /// キーボードから手番の操作を入力させる．
+    /// 矢印キーでカーソルを動かし，Enterキーで動かす👻を選ぶと，その👻の移動先が盤面に示される．
+    /// もう一度Enterキーを押すと，カーソルのあるマスへ👻を動かす．
+    /// 選んだ👻の上で押した場合は，その👻をフィールドから上がらせる．
+    /// 行動はルール上とれるものだけを受け付ける．
+    /// # Params
+    /// 1. `last_action` 盤面に目印を付ける，直前の行動．
+    /// 1. `notice` 盤面に添えて表示する，直前の行動や操作の結果などの知らせ．
+    fn select_command(
+        &self,
+        view: &PlayerView,
+        actor: Actor,
+        last_action: Option<&GeisterAction>,
+        notice: Option<&str>,
+    ) -> PlayerCommand {
+        let available_actions = view.available_actions();
+        // このゲームでは必ずひとつ以上の行動がとれるはず．
+        assert!(!available_actions.is_empty());
+
+        // 最初は行動できる👻のいずれかにカーソルを置く
+        let mut cursor = BoardCursor::new(available_actions[0].target_geister_position);
+        // 直前の入力が受け付けられなかった理由など，次の入力までの間だけ表示する知らせ
+        let mut message = None;
 
         loop {
-            // 何番目の👻を動かすか
-            let i: usize = ui::input_parsable("Input an index of which you want to move");
-
-            let action = match own_geister_positions.get(i) {
-                // 指定した番号の👻が存在すれば，移動方法の入力へ移る
-                Some(&target_geister_position) => {
-                    println!("Type a direction to move, or Enter to clear");
-                    let geister_movement = match self.keyboard_input.read_key().ok()? {
-                        Key::ArrowUp => GeisterMovement::Direction(Pair::new(0, -1)),
-                        Key::ArrowDown => GeisterMovement::Direction(Pair::new(0, 1)),
-                        Key::ArrowLeft => GeisterMovement::Direction(Pair::new(-1, 0)),
-                        Key::ArrowRight => GeisterMovement::Direction(Pair::new(1, 0)),
-                        Key::Enter => GeisterMovement::Clear,
+            clear_screen();
+            println!("{:?}'s turn", actor);
+            match write_view_with_cursor(view, &cursor, last_action) {
+                Ok(s) => println!("{}", s),
+                Err(e) => println!("An error was occurred during writing field: {}", e),
+            }
+            if let Some(notice) = notice {
+                println!("{}", notice);
+            }
+            if let Some(message) = message.take() {
+                println!("{}", message);
+            }
+            match cursor.selected {
+                Some(_) if cursor.can_clear => println!(
+                    "Arrow keys to move, Enter to move there (on the geister marked ^ to clear), Backspace to cancel"
+                ),
+                Some(_) => println!(
+                    "Arrow keys to move, Enter to move there, Backspace to cancel"
+                ),
+                None => println!(
+                    "Arrow keys to move, Enter to pick a geister, i to type an action, u to undo, Esc to resign"
+                ),
+            }
+
+            let key = match self.keyboard_input.read_key() {
+                Ok(key) => key,
+                Err(_) => return PlayerCommand::Resign,
+            };
+            let direction = match key {
+                Key::ArrowUp => Pair::new(0, -1),
+                Key::ArrowDown => Pair::new(0, 1),
+                Key::ArrowLeft => Pair::new(-1, 0),
+                Key::ArrowRight => Pair::new(1, 0),
+                Key::Enter => {
+                    let action = match cursor.selected {
+                        // 選んだ👻の上で押すと上がり，それ以外のマスでは移動となる
+                        Some(selected) if selected == cursor.position => {
+                            GeisterAction::new(selected, GeisterMovement::Clear, actor)
+                        }
+                        Some(selected) if cursor.destinations.contains(&cursor.position) => {
+                            *available_actions
+                                .iter()
+                                .find(|action| {
+                                    action.target_geister_position == selected
+                                        && action.position_after_movement() == Some(cursor.position)
+                                })
+                                .expect("Destination must come from an available action")
+                        }
+                        // 移動先ではないマスで押した場合は，そこにいる👻を選び直す
                         _ => {
-                            println!("Invalid keyboard input");
+                            if available_actions
+                                .iter()
+                                .any(|action| action.target_geister_position == cursor.position)
+                            {
+                                cursor.select(cursor.position, &available_actions);
+                            } else {
+                                cursor.deselect();
+                                message =
+                                    Some("Choose a geister of yours that can move".to_string());
+                            }
                             continue;
                         }
                     };
-                    GeisterAction::new(target_geister_position, geister_movement, actor)
+                    // 入力された行動が実行可能なものであれば，それを返す．
+                    // そうでない場合は理由を示して，もう一度入力をやり直させる．
+                    match view.validate(&action) {
+                        Ok(()) => return PlayerCommand::Act(action),
+                        Err(e) => {
+                            message = Some(format!("Unavailable action: {}", e));
+                            continue;
+                        }
+                    }
                 }
-                None => {
-                    println!("Invalid index");
+                // 👻の選択を取り消す．押し過ぎて行動まで取り消さないよう，行動の取り消しは`u`キーだけで行う
+                Key::Backspace => {
+                    cursor.deselect();
+                    continue;
+                }
+                Key::Char('u') => return PlayerCommand::Undo,
+                // `b1-b2`のような相対表記で行動を直接入力する
+                Key::Char('i') => {
+                    let input: String = ui::input_parsable("Input an action such as b1-b2");
+                    match ActionNotation::parse_action_for(input.trim(), view) {
+                        Ok(action) => return PlayerCommand::Act(action),
+                        Err(e) => {
+                            message = Some(e);
+                            continue;
+                        }
+                    }
+                }
+                // 誤って投了しないよう，確認してから投了する
+                Key::Escape => {
+                    println!("Press y to resign, or any other key to continue");
+                    if matches!(self.keyboard_input.read_key(), Ok(Key::Char('y'))) {
+                        return PlayerCommand::Resign;
+                    }
+                    continue;
+                }
+                _ => {
+                    message = Some("Invalid keyboard input".to_string());
                     continue;
                 }
             };
 
-            // 入力された行動が実行可能なものであれば，それを返す．
-            // そうでない場合はもう一度入力をやり直させる．
-            if let Some(_) = available_actions.iter().filter(|&&a| a == action).next() {
-                return Some(action);
-            } else {
-                println!("Unavailable action");
-                continue;
+            // カーソルは画面上で矢印キーの向きに，フィールド内だけを動く
+            let direction = direction_on_field(direction, actor);
+            let position = cursor
+                .position
+                .try_cast::<isize>()
+                .ok()
+                .and_then(|p| (p + direction).try_cast::<usize>().ok());
+            if let Some(position) = position {
+                if view.lattices.is_valid_index(position) {
+                    cursor.position = position;
+                }
             }
         }
     }
 }
 
-/// 👻の初期位置をランダムに決定する．
-fn select_initial_positions() -> HashMap<OwnedGeister, Vec<TableIndex>> {
-    let mut hash_map = HashMap::new();
+impl Strategy<PlayerView, GeisterAction> for PlayerStrategy {
+    fn select_action(&self, view: &PlayerView, actor: Actor) -> Option<GeisterAction> {
+        // 行動を取り消すには対局の履歴が必要なため，ここでは受け付けない
+        let mut notice = None;
+        loop {
+            match self.select_command(view, actor, None, notice) {
+                PlayerCommand::Act(action) => return Some(action),
+                PlayerCommand::Undo => notice = Some("Undo is not available"),
+                PlayerCommand::Resign => return None,
+            }
+        }
+    }
+}
 
-    for &actor in actors().iter() {
-        // 配置可能な位置を決定する．
-        // 各プレイヤー，最も自分側よりの2行で，かつ端以外の列にあるマスに👻を配置できる．
-        let mut available_positions = (1..FIELD_SIZE.x - 1)
-            .flat_map(|x| {
-                let y_range = match actor {
-                    Actor::First => FIELD_SIZE.y - 2..FIELD_SIZE.y,
-                    Actor::Second => 0..2,
+/// 人間は初期配置の編集画面で，自分の👻の初期配置を決める．
+impl InitialPlacement for PlayerStrategy {
+    fn select_setup(&self, rules: &GeisterRules, actor: Actor, rng: &mut dyn RngCore) -> Setup {
+        edit_initial_setup(&self.keyboard_input, self.hot_seat, rules, actor, rng)
+    }
+}
+
+/// 人間のプレイヤーに，自分の👻の初期配置を編集させる．
+/// 矢印キーでカーソルを動かし，スペースキーで選んだ二つのマスの👻を入れ替える．
+/// `r`キーでランダムな配置に戻し，Enterキーで配置を確定する．
+/// # Params
+/// 1. `hot_seat` 人間同士が一つの端末を交代で使う対局かどうか．そうであれば，編集の前に端末を本人に渡してもらう．
+fn edit_initial_setup(
+    keyboard_input: &KeyboardInput,
+    hot_seat: bool,
+    rules: &GeisterRules,
+    actor: Actor,
+    rng: &mut dyn RngCore,
+) -> Setup {
+    let setup_positions = rules.setup_positions_of(actor);
+    let mut setup = RandomPlacement.select_setup(rules, actor, rng);
+    let mut cursor = setup_positions[0];
+    // 入れ替えの対象として選ばれたマス
+    let mut selected_position = None;
+    // 配置を確定できなかった理由など，次の入力までの間だけ表示する知らせ
+    let mut message = None;
+
+    // 相手プレイヤーに配置を見られないよう，本人が席に着いてから表示する
+    if hot_seat {
+        hand_off_to(actor, keyboard_input);
+    }
+
+    loop {
+        clear_screen();
+        println!("{:?}'s setup", actor);
+        match write_setup(&setup, rules, cursor, selected_position) {
+            Ok(s) => println!("{}", s),
+            Err(e) => println!("An error was occurred during writing field: {}", e),
+        }
+        if let Some(message) = message.take() {
+            println!("{}", message);
+        }
+        println!("Arrow keys to move, Space to swap, r to randomize, Enter to finish");
+
+        let direction = match keyboard_input.read_key() {
+            Ok(Key::ArrowUp) => Pair::new(0, -1),
+            Ok(Key::ArrowDown) => Pair::new(0, 1),
+            Ok(Key::ArrowLeft) => Pair::new(-1, 0),
+            Ok(Key::ArrowRight) => Pair::new(1, 0),
+            Ok(Key::Char(' ')) => {
+                // 二つ目のマスを選んだら，一つ目のマスの👻と入れ替える
+                selected_position = match selected_position {
+                    Some(position) => {
+                        setup.swap(position, cursor);
+                        None
+                    }
+                    None => Some(cursor),
                 };
-                y_range.map(move |y| TableIndex::new(x, y))
-            })
-            .collect::<Vec<_>>();
-        assert!(available_positions.len() >= INITIAL_GEISTER_COUNT * 2);
-        // 列挙した配置可能位置をランダムな順序にする．
-        available_positions.shuffle(&mut rand::thread_rng());
+                continue;
+            }
+            Ok(Key::Char('r')) => {
+                setup = RandomPlacement.select_setup(rules, actor, rng);
+                selected_position = None;
+                continue;
+            }
+            Ok(Key::Enter) => match setup.validate(rules) {
+                Ok(()) => break,
+                Err(e) => {
+                    message = Some(e.to_string());
+                    continue;
+                }
+            },
+            _ => continue,
+        };
 
-        // 配置可能位置の配列から👻の初期個数ずつ要素を取り出して，各👻の初期位置とする．
-        for (&geister, positions) in geisters()
-            .iter()
-            .zip(available_positions.chunks_exact(INITIAL_GEISTER_COUNT))
-        {
-            let owned_geister = OwnedGeister::new(geister, actor);
-            let positions = positions.iter().map(|&p| p).collect();
-            hash_map.insert(owned_geister, positions);
+        // カーソルは画面上で矢印キーの向きに，初期配置の領域内だけを動く
+        let direction = direction_on_field(direction, actor);
+        let position = cursor
+            .try_cast::<isize>()
+            .ok()
+            .and_then(|p| (p + direction).try_cast::<usize>().ok());
+        if let Some(position) = position {
+            if setup_positions.contains(&position) {
+                cursor = position;
+            }
         }
     }
 
-    hash_map
+    // 次のプレイヤーに配置を見られないよう，表示内容をクリア
+    clear_screen();
+    setup
+}
+
+/// 編集中の初期配置を表示する文字列を返す．
+/// カーソルのあるマスには`<`を，入れ替えの対象として選ばれたマスには`*`を添える．
+fn write_setup(
+    setup: &Setup,
+    rules: &GeisterRules,
+    cursor: TableIndex,
+    selected_position: Option<TableIndex>,
+) -> Result<String, DrawError> {
+    let setup_positions = rules.setup_positions_of(setup.actor());
+    let size = rules.field_size();
+    let mut s = String::new();
+    // 初期配置を決めるプレイヤーの陣地が下側に来るよう表示する
+    for screen_y in 0..size.y {
+        for screen_x in 0..size.x {
+            let position =
+                position_on_screen(TableIndex::new(screen_x, screen_y), size, setup.actor());
+            let right_char = if position == cursor {
+                '<'
+            } else if Some(position) == selected_position {
+                '*'
+            } else {
+                ' '
+            };
+            let unit = match setup.geister_at(position) {
+                Some(geister) => {
+                    drawable_unit_of(OwnedGeister::new(geister, setup.actor()), right_char)
+                }
+                // 初期配置の領域内の空きマスと，領域外のマスを区別する
+                None if setup_positions.contains(&position) => {
+                    DrawableUnit::from_double_half_char('.', right_char, UnitColor::White)
+                }
+                None => DrawableUnit::from_double_half_char('-', '-', UnitColor::White),
+            };
+            unit.write_to(&mut s)?;
+        }
+        s += "\n";
+    }
+    Ok(s)
 }
 
 /// 指定した👻を表示する際の文字を返す．
 /// # Params
 /// 1. `owned_geister` 表示対象の👻．
-/// 1. `geister_index` 👻を番号付きで表示したい場合に`Some(i)`として指定する．
-/// この番号付けは，ユーザーが行動を選択するときに👻を区別するために使用する．
-fn drawable_unit_of(owned_geister: OwnedGeister, geister_index: Option<usize>) -> DrawableUnit {
+/// 1. `right_char` 👻の種類を表す文字の右に添える文字．カーソルなどの目印に使う．
+fn drawable_unit_of(owned_geister: OwnedGeister, right_char: char) -> DrawableUnit {
     let left_char = match owned_geister.geister {
         Geister::Holy => 'H',
         Geister::Evil => 'E',
     };
-    let right_char = match geister_index {
-        Some(i) => i.to_string().chars().nth(0).unwrap(),
-        None => ' ',
-    };
-    let color = match owned_geister.owner {
+    DrawableUnit::from_double_half_char(
+        left_char,
+        right_char,
+        drawable_unit_color_of(owned_geister),
+    )
+}
+
+/// 指定した👻を表示する際の色を返す．
+fn drawable_unit_color_of(owned_geister: OwnedGeister) -> UnitColor {
+    match owned_geister.owner {
         Actor::First => match owned_geister.geister {
             Geister::Holy => UnitColor::Blue,
             Geister::Evil => UnitColor::Red,
@@ -135,18 +424,75 @@ fn drawable_unit_of(owned_geister: OwnedGeister, geister_index: Option<usize>) -
             Geister::Holy => UnitColor::Cyan,
             Geister::Evil => UnitColor::Magenta,
         },
-    };
-    DrawableUnit::from_double_half_char(left_char, right_char, color)
+    }
 }
 
 /// 指定したプレイヤー視点から見た場合のゲーム状態を返す．
 /// # Params
 /// 1. `state` ゲーム状態
 /// 1. `viewpoint_actor` 視点プレイヤーを`Some(p)`として指定する．
-/// 神視点から見た状態を返したい場合は`None`とする．
+///    神視点から見た状態を返したい場合は`None`とする．
+/// 1. `last_action` 直前の行動に目印を付けたい場合に`Some(action)`として指定する．
 fn write_state_for(
     state: &GeisterState,
     viewpoint_actor: Option<Actor>,
+    last_action: Option<&GeisterAction>,
+) -> Result<String, DrawError> {
+    write_field(
+        state.rules(),
+        viewpoint_actor,
+        &|owned_geister| state.killed_geister_count(owned_geister),
+        &|position| {
+            state.geister_at(position).map(|owned_geister| {
+                // 視点プレイヤー以外の👻の種類は隠す
+                let is_visible = viewpoint_actor.is_none_or(|actor| actor == owned_geister.owner);
+                ObservedGeister {
+                    owner: owned_geister.owner,
+                    geister: Some(owned_geister.geister).filter(|_| is_visible),
+                }
+            })
+        },
+        None,
+        last_action,
+    )
+}
+
+/// 観測者から見たゲーム状態を，カーソルと選ばれた👻の移動先や上がれるかどうか，直前の行動を添えて返す．
+fn write_view_with_cursor(
+    view: &PlayerView,
+    cursor: &BoardCursor,
+    last_action: Option<&GeisterAction>,
+) -> Result<String, DrawError> {
+    write_field(
+        view.rules(),
+        Some(view.viewpoint()),
+        &|owned_geister| view.killed_geister_count(owned_geister),
+        &|position| view.lattices[position],
+        Some(cursor),
+        last_action,
+    )
+}
+
+/// 取り除かれた👻の数とフィールドを表示する文字列を返す．
+/// カーソルのあるマスには`<`を，選ばれた👻のマスには`*`を，その👻の移動先には`+`を添える．
+/// 選ばれた👻がフィールドから上がれる場合は，そのマスに`*`の代わりに`^`を添える．
+/// 直前の行動で👻が動く前のマスには`.`を，動いた後のマスには`!`を添える．
+/// ひとつのマスに複数の目印が重なる場合は，カーソルに関する目印を優先する．
+/// # Params
+/// 1. `rules` ゲームのルール．
+/// 1. `viewpoint_actor` 視点プレイヤー．このプレイヤーの陣地が下側に来るよう表示し，
+///    行・列もこのプレイヤーから見た相対表記で示す．神視点の場合は`None`とし，先手から見て表示する．
+/// 1. `killed_geister_count` 指定した👻が何体取り除かれたか返す関数．
+/// 1. `observed_geister_at` 指定したマスに表示する👻を返す関数．
+/// 1. `cursor` カーソルを表示したい場合に`Some(cursor)`として指定する．
+/// 1. `last_action` 直前の行動に目印を付けたい場合に`Some(action)`として指定する．
+fn write_field(
+    rules: &GeisterRules,
+    viewpoint_actor: Option<Actor>,
+    killed_geister_count: &dyn Fn(OwnedGeister) -> usize,
+    observed_geister_at: &dyn Fn(TableIndex) -> Option<ObservedGeister>,
+    cursor: Option<&BoardCursor>,
+    last_action: Option<&GeisterAction>,
 ) -> Result<String, DrawError> {
     let mut s = String::new();
     // 各プレイヤーの取り除かれた👻の数を表示
@@ -162,10 +508,9 @@ fn write_state_for(
 
         for &geister in geisters().iter() {
             let owned_geister = OwnedGeister::new(geister, actor);
-            let killed_geister_count = state.killed_geister_count(owned_geister);
-            drawable_unit_of(owned_geister, None).write_to(&mut s)?;
+            drawable_unit_of(owned_geister, ' ').write_to(&mut s)?;
             for c in DrawableUnit::create_units_from(
-                &format!(": {}  ", killed_geister_count),
+                &format!(": {}  ", killed_geister_count(owned_geister)),
                 UnitColor::White,
             )
             .into_iter()
@@ -176,25 +521,64 @@ fn write_state_for(
         s += "\n";
     }
 
-    // フィールドを表示
-    let mut index = 0;
-    for row in state.lattices.iter_row() {
-        for &lattice in row.iter() {
+    // フィールドを表示．各行の左に行の番号を，最後の行の下に列の英字を添える
+    let size = rules.field_size();
+    for screen_y in 0..size.y {
+        let rank = format!("{:<2}", size.y - screen_y);
+        let mut rank_chars = rank.chars();
+        DrawableUnit::from_double_half_char(
+            rank_chars.next().unwrap_or(' '),
+            rank_chars.next().unwrap_or(' '),
+            UnitColor::White,
+        )
+        .write_to(&mut s)?;
+        for screen_x in 0..size.x {
+            let position = position_on_screen(
+                TableIndex::new(screen_x, screen_y),
+                size,
+                viewpoint_actor.unwrap_or(Actor::First),
+            );
+            // マスに添える目印
+            let marker = match cursor {
+                Some(cursor) if cursor.position == position => Some('<'),
+                Some(cursor) if cursor.selected == Some(position) && cursor.can_clear => Some('^'),
+                Some(cursor) if cursor.selected == Some(position) => Some('*'),
+                Some(cursor) if cursor.destinations.contains(&position) => Some('+'),
+                _ => match last_action {
+                    Some(action) if action.target_geister_position == position => Some('.'),
+                    Some(action) if action.position_after_movement() == Some(position) => Some('!'),
+                    _ => None,
+                },
+            };
             // 各マスに何を表示するか決定する．
-            let unit = match lattice {
-                Some(owned_geister) => match viewpoint_actor {
-                    Some(viewpoint_actor) => {
-                        if owned_geister.owner == viewpoint_actor {
-                            let unit = drawable_unit_of(owned_geister, Some(index));
-                            index += 1;
-                            unit
-                        } else {
-                            DrawableUnit::from_double_half_char('?', ' ', UnitColor::White)
-                        }
+            let unit = match observed_geister_at(position) {
+                Some(ObservedGeister {
+                    owner,
+                    geister: Some(geister),
+                }) => drawable_unit_of(OwnedGeister::new(geister, owner), marker.unwrap_or(' ')),
+                Some(ObservedGeister { geister: None, .. }) => DrawableUnit::from_double_half_char(
+                    '?',
+                    marker.unwrap_or(' '),
+                    UnitColor::White,
+                ),
+                None => {
+                    // 空いている脱出マスは，そこから上がれるプレイヤーの色で表示する
+                    match actors()
+                        .iter()
+                        .find(|&&actor| rules.clearable_positions_of(actor).contains(&position))
+                    {
+                        Some(&actor) => DrawableUnit::from_double_half_char(
+                            '[',
+                            marker.unwrap_or(']'),
+                            drawable_unit_color_of(OwnedGeister::new(Geister::Holy, actor)),
+                        ),
+                        None => DrawableUnit::from_double_half_char(
+                            '-',
+                            marker.unwrap_or('-'),
+                            UnitColor::White,
+                        ),
                     }
-                    None => drawable_unit_of(owned_geister, None),
-                },
-                None => DrawableUnit::from_double_half_char('-', '-', UnitColor::White),
+                }
             };
             // 表示
             unit.write_to(&mut s)?;
@@ -202,45 +586,668 @@ fn write_state_for(
         // 一行表示し終わったら改行
         s += "\n";
     }
+    DrawableUnit::from_double_half_char(' ', ' ', UnitColor::White).write_to(&mut s)?;
+    for screen_x in 0..size.x {
+        let file = std::char::from_u32('a' as u32 + screen_x as u32).unwrap_or('?');
+        DrawableUnit::from_double_half_char(file, ' ', UnitColor::White).write_to(&mut s)?;
+    }
+    s += "\n";
 
     Ok(s)
 }
 
+/// 画面上の位置と，フィールド上の位置とを相互に変換する．
+/// 視点プレイヤーの陣地が画面の下側に来るよう，後手から見る場合はフィールドを180度回転させる．
+fn position_on_screen(position: TableIndex, field_size: TableSize, viewpoint: Actor) -> TableIndex {
+    match viewpoint {
+        Actor::First => position,
+        Actor::Second => {
+            TableIndex::new(field_size.x - 1 - position.x, field_size.y - 1 - position.y)
+        }
+    }
+}
+
+/// 画面上の向きを，フィールド上の向きに変換する．
+/// 後手から見た画面はフィールドを180度回転させたものなので，向きも反転させる．
+fn direction_on_field(screen_direction: Pair<isize>, viewpoint: Actor) -> Pair<isize> {
+    match viewpoint {
+        Actor::First => screen_direction,
+        Actor::Second => Pair::new(-screen_direction.x, -screen_direction.y),
+    }
+}
+
+/// 棋譜を1手ずつ再生する．
+/// 左右の矢印キーで1手ずつ戻る・進み，上下の矢印キーで最初・最後の局面へ移る．
+/// `g`，`f`，`s`キーで神視点・先手視点・後手視点を切り替え，`q`キーで終了する．
+fn replay(record: &GameRecord, keyboard_input: &KeyboardInput) {
+    let last_ply = record.actions().len();
+    let mut ply = 0;
+    let mut viewpoint = None;
+
+    loop {
+        let state = record.state_at(ply).expect("Ply must be within the record");
+        clear_screen();
+        println!(
+            "{} vs {}  {}",
+            record.tags().first.as_deref().unwrap_or("?"),
+            record.tags().second.as_deref().unwrap_or("?"),
+            record
+                .outcome()
+                .map(|outcome| outcome.to_string())
+                .unwrap_or_else(|| "*".to_string())
+        );
+        match viewpoint {
+            Some(actor) => println!("Ply {}/{}, viewed by {:?}", ply, last_ply, actor),
+            None => println!("Ply {}/{}", ply, last_ply),
+        }
+        let last_action = ply.checked_sub(1).map(|i| &record.actions()[i]);
+        match write_state_for(&state, viewpoint, last_action) {
+            Ok(s) => println!("{}", s),
+            Err(e) => {
+                println!("An error was occurred during writing field: {}", e);
+                return;
+            }
+        }
+        println!("{}", write_move_list(record, ply));
+        for line in write_capture_events(record, ply) {
+            println!("{}", line);
+        }
+        println!("Left/Right to step, Up/Down to jump, g/f/s to switch view, q to quit");
+
+        match keyboard_input.read_key() {
+            Ok(Key::ArrowLeft) => ply = ply.saturating_sub(1),
+            Ok(Key::ArrowRight) => ply = (ply + 1).min(last_ply),
+            Ok(Key::ArrowUp) => ply = 0,
+            Ok(Key::ArrowDown) => ply = last_ply,
+            Ok(Key::Char('g')) => viewpoint = None,
+            Ok(Key::Char('f')) => viewpoint = Some(Actor::First),
+            Ok(Key::Char('s')) => viewpoint = Some(Actor::Second),
+            Ok(Key::Char('q')) | Ok(Key::Escape) | Err(_) => return,
+            _ => {}
+        }
+    }
+}
+
+/// 棋譜の行動を絶対表記で並べた文字列を返す．
+/// 指定した手数までに適用した最後の行動を`[]`で囲む．
+fn write_move_list(record: &GameRecord, ply: usize) -> String {
+    let field_size = record.initial_state().rules().field_size();
+    let mut s = String::new();
+    for (i, action) in record.actions().iter().enumerate() {
+        if action.actor == Actor::First {
+            s += &format!("{}. ", (record.initial_state().ply() + i) / 2 + 1);
+        }
+        let notation = ActionNotation::of(action, field_size, Actor::First);
+        if i + 1 == ply {
+            s += &format!("[{}] ", notation);
+        } else {
+            s += &format!("{} ", notation);
+        }
+    }
+    s
+}
+
+/// 指定した手数までに👻が取られたことを，1件1行で説明する．
+fn write_capture_events(record: &GameRecord, ply: usize) -> Vec<String> {
+    let field_size = record.initial_state().rules().field_size();
+    record
+        .captures()
+        .iter()
+        .filter(|capture| capture.ply < ply)
+        .map(|capture| {
+            let action = record.actions()[capture.ply];
+            format!(
+                "Ply {}: {:?} captured {:?}'s {:?} geister with {}",
+                capture.ply + 1,
+                capture.capturer(),
+                capture.captured_geister.owner,
+                capture.captured_geister.geister,
+                ActionNotation::of(&action, field_size, Actor::First)
+            )
+        })
+        .collect()
+}
+
+/// 視点プレイヤーが前回行動してから後の行動と，それによって👻が取られたことを説明する文字列を，1手1行で返す．
+/// 神視点では直前の行動だけを説明する．
+/// マスは視点プレイヤーから見た相対表記で表し，神視点では絶対表記で表す．
+fn write_recent_events(record: &GameRecord, viewpoint: Option<Actor>) -> Vec<String> {
+    let actions = record.actions();
+    let field_size = record.initial_state().rules().field_size();
+    let square_of = |position| {
+        ActionNotation::square_of(position, field_size, viewpoint.unwrap_or(Actor::First))
+    };
+    let name_of = |actor| {
+        if Some(actor) == viewpoint {
+            "You".to_string()
+        } else {
+            format!("{:?}", actor)
+        }
+    };
+    let since = match viewpoint {
+        Some(viewpoint) => actions
+            .iter()
+            .rposition(|action| action.actor == viewpoint)
+            .unwrap_or(0),
+        None => actions.len().saturating_sub(1),
+    };
+
+    let mut lines = vec![];
+    for (ply, action) in actions.iter().enumerate().skip(since) {
+        let from = square_of(action.target_geister_position);
+        lines.push(match action.position_after_movement() {
+            Some(to) => format!("{} moved {}→{}", name_of(action.actor), from, square_of(to)),
+            None => format!("{} cleared a geister from {}", name_of(action.actor), from),
+        });
+        if let Some(capture) = record.captures().iter().find(|capture| capture.ply == ply) {
+            let captured = capture.captured_geister;
+            let article = match captured.geister {
+                Geister::Holy => "a",
+                Geister::Evil => "an",
+            };
+            let whose = if Some(captured.owner) == viewpoint {
+                "your".to_string()
+            } else if Some(capture.capturer()) == viewpoint {
+                article.to_string()
+            } else {
+                format!("{:?}'s", captured.owner)
+            };
+            lines.push(format!(
+                "{} captured {} {:?} geister at {}",
+                name_of(capture.capturer()),
+                whose,
+                captured.geister,
+                square_of(capture.position)
+            ));
+        }
+    }
+    lines
+}
+
+/// 指定したプレイヤーの直前の行動と，その後の相手の行動を取り消し，結果を説明する文字列を返す．
+/// 相手も人間の場合は，相手が承諾したときだけ取り消す．
+fn undo_last_action_of(
+    record: &mut GameRecord,
+    actor: Actor,
+    options: &GameOptions,
+    keyboard_input: &KeyboardInput,
+) -> String {
+    if record.actions().iter().all(|action| action.actor != actor) {
+        return "There is no action to undo".to_string();
+    }
+
+    let opponent = actor.opponent();
+    if options.player_kind_of(opponent) == PlayerKind::Human {
+        // 盤面を隠してから相手プレイヤーに確認する
+        hand_off_to(opponent, keyboard_input);
+        println!(
+            "{:?} wants to undo. {:?}, press y to accept or any other key to refuse",
+            actor, opponent
+        );
+        let accepted = matches!(keyboard_input.read_key(), Ok(Key::Char('y')));
+        // 確認が済んだら，取り消しを求めたプレイヤーに端末を返す
+        hand_off_to(actor, keyboard_input);
+        if !accepted {
+            return format!("{:?} refused to undo", opponent);
+        }
+    }
+
+    // 指定したプレイヤーの行動を取り消すまで，行動を新しい順に取り消す
+    while let Some(action) = record.pop() {
+        if action.actor == actor {
+            break;
+        }
+    }
+    format!("{:?} took back the last action", actor)
+}
+
+/// 端末の表示内容を，スクロールして戻れる範囲も含めて消去し，カーソルを左上に移す．
+fn clear_screen() {
+    print!("\x1B[3J\x1B[2J\x1B[H");
+    io::stdout().flush().ok();
+}
+
+/// 画面を消去して指定したプレイヤーに端末を渡すよう促し，キーが押されるまで待つ．
+/// 前のプレイヤーに次のプレイヤーの情報を見られないよう，情報を表示する前に呼ぶ．
+fn hand_off_to(actor: Actor, keyboard_input: &KeyboardInput) {
+    clear_screen();
+    println!("Pass to {:?}, press any key", actor);
+    keyboard_input.read_key().ok();
+    clear_screen();
+}
+
+/// 端末の代替画面への切り替えを表す．
+/// 生成すると代替画面に切り替え，破棄すると元の画面に戻す．
+/// 対局中の表示は代替画面で行い，対局後に元の画面へ情報が残らないようにする．
+struct AlternateScreen;
+
+impl AlternateScreen {
+    /// 代替画面に切り替える．
+    fn enter() -> Self {
+        print!("\x1B[?1049h");
+        io::stdout().flush().ok();
+        AlternateScreen
+    }
+}
+
+impl Drop for AlternateScreen {
+    fn drop(&mut self) {
+        clear_screen();
+        print!("\x1B[?1049l");
+        io::stdout().flush().ok();
+    }
+}
+
+/// 席に着くプレイヤーの種類．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayerKind {
+    /// キーボードから行動を入力する人間．
+    Human,
+    /// コンピュータ．
+    Computer,
+}
+
+impl FromStr for PlayerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(PlayerKind::Human),
+            "ai" => Ok(PlayerKind::Computer),
+            _ => Err(format!("Unknown player kind: {}", s)),
+        }
+    }
+}
+
+impl Display for PlayerKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            PlayerKind::Human => write!(f, "human"),
+            PlayerKind::Computer => write!(f, "ai"),
+        }
+    }
+}
+
+/// 初期配置の決め方の種類．
+#[derive(Debug, Clone, PartialEq)]
+enum SetupPolicy {
+    /// 初期配置の編集画面で人間が決める．
+    Edit,
+    /// `RandomPlacement`．
+    Random,
+    /// `HolyFrontPlacement`．
+    HolyFront,
+    /// `HolyFlankPlacement`．
+    HolyFlank,
+    /// ファイルから読み込んだ重みを使う`WeightedRandomPlacement`．
+    WeightedRandom(WeightedRandomPlacement),
+}
+
+/// `edit`，`random`，`front`，`flank`，`weighted:path/to/weights.txt`のいずれかの表記から生成する．
+impl FromStr for SetupPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "edit" => Ok(SetupPolicy::Edit),
+            "random" => Ok(SetupPolicy::Random),
+            "front" => Ok(SetupPolicy::HolyFront),
+            "flank" => Ok(SetupPolicy::HolyFlank),
+            _ if s.starts_with("weighted:") => {
+                WeightedRandomPlacement::load(&s["weighted:".len()..])
+                    .map(SetupPolicy::WeightedRandom)
+            }
+            _ => Err(format!("Unknown setup policy: {}", s)),
+        }
+    }
+}
+
+/// コマンドラインから指定できる対局の設定．
+struct GameOptions {
+    /// ゲームのルール．
+    rules: GeisterRules,
+    /// 先手のプレイヤーの種類．
+    first: PlayerKind,
+    /// 後手のプレイヤーの種類．
+    second: PlayerKind,
+    /// 先手の初期配置の決め方．
+    first_setup: SetupPolicy,
+    /// 後手の初期配置の決め方．
+    second_setup: SetupPolicy,
+    /// コンピュータが探索する手数．
+    depth: usize,
+    /// 初期配置やコンピュータの行動を決める乱数の種．
+    seed: u64,
+    /// 対局後に棋譜を保存するファイル．
+    save_path: Option<String>,
+}
+
+impl GameOptions {
+    /// コンピュータが探索する手数の既定値．
+    const DEFAULT_DEPTH: usize = 3;
+
+    /// コマンドライン引数から対局の設定を読み取る．
+    /// - `--rules 8x8`，`--max-ply 300` ルール．`rules_from_args()`を参照．
+    /// - `--first ai`，`--second human` 各手番のプレイヤーの種類 (`human`または`ai`)．
+    /// - `--first-setup front`，`--second-setup weighted:weights.txt` 各手番の初期配置の決め方．
+    ///   指定がなければ，人間は編集画面で決め，コンピュータはランダムに決める．
+    /// - `--depth 3` コンピュータが探索する手数．
+    /// - `--seed 42` 初期配置やコンピュータの行動を決める乱数の種．指定がなければランダムに決める．
+    /// - `--save game.txt` 対局後に棋譜を保存するファイル．
+    fn from_args() -> Result<Self, String> {
+        let rules = rules_from_args()?;
+        let first = parse_option("--first")?.unwrap_or(PlayerKind::Human);
+        let second = parse_option("--second")?.unwrap_or(PlayerKind::Human);
+        let setup_policy = |name, player_kind| -> Result<SetupPolicy, String> {
+            let setup_policy = parse_option(name)?.unwrap_or(match player_kind {
+                PlayerKind::Human => SetupPolicy::Edit,
+                PlayerKind::Computer => SetupPolicy::Random,
+            });
+            if let SetupPolicy::WeightedRandom(ref placement) = setup_policy {
+                placement.check_size_for(&rules)?;
+            }
+            Ok(setup_policy)
+        };
+
+        Ok(Self {
+            rules,
+            first,
+            second,
+            first_setup: setup_policy("--first-setup", first)?,
+            second_setup: setup_policy("--second-setup", second)?,
+            depth: parse_option("--depth")?.unwrap_or(Self::DEFAULT_DEPTH),
+            seed: parse_option("--seed")?.unwrap_or_else(rand::random),
+            save_path: option_value("--save"),
+        })
+    }
+
+    /// 指定した手番のプレイヤーの種類を返す．
+    fn player_kind_of(&self, actor: Actor) -> PlayerKind {
+        match actor {
+            Actor::First => self.first,
+            Actor::Second => self.second,
+        }
+    }
+
+    /// 棋譜に記録する，指定した手番のプレイヤーの説明を返す．
+    fn player_description_of(&self, actor: Actor) -> String {
+        match self.player_kind_of(actor) {
+            PlayerKind::Human => PlayerKind::Human.to_string(),
+            PlayerKind::Computer => format!("{} (depth {})", PlayerKind::Computer, self.depth),
+        }
+    }
+
+    /// 指定した手番のプレイヤーの初期配置の決め方を返す．
+    fn setup_policy_of(&self, actor: Actor) -> &SetupPolicy {
+        match actor {
+            Actor::First => &self.first_setup,
+            Actor::Second => &self.second_setup,
+        }
+    }
+
+    /// 人間同士が一つの端末を交代で使う対局かどうか返す．
+    fn is_hot_seat(&self) -> bool {
+        self.first == PlayerKind::Human && self.second == PlayerKind::Human
+    }
+
+    /// 指定した手番で表示すべき視点を返す．
+    /// 人間同士なら手番のプレイヤー，人間がひとりならその人間，コンピュータ同士なら神視点とする．
+    fn viewpoint_on_turn_of(&self, actor: Actor) -> Option<Actor> {
+        match (self.first, self.second) {
+            (PlayerKind::Human, PlayerKind::Human) => Some(actor),
+            (PlayerKind::Human, PlayerKind::Computer) => Some(Actor::First),
+            (PlayerKind::Computer, PlayerKind::Human) => Some(Actor::Second),
+            (PlayerKind::Computer, PlayerKind::Computer) => None,
+        }
+    }
+}
+
+/// コマンドライン引数からゲームのルールを読み取る．
+/// - `--rules 8x8` ルール．指定がなければ標準ルールを使う．
+/// - `--max-ply 300` 引き分けとなる手数．`0`を指定すると手数の上限を設けない．
+///   `--rules`の表記にも指定がなければ，コンピュータ同士の対局が終わらなくならないよう，
+///   `GeisterRules::DEFAULT_MAX_PLY`手で引き分けとする．
+fn rules_from_args() -> Result<GeisterRules, String> {
+    let rules = parse_option::<GeisterRules>("--rules")?.unwrap_or_default();
+    let max_ply = match parse_option::<usize>("--max-ply")? {
+        Some(0) => None,
+        Some(max_ply) => Some(max_ply),
+        None => Some(rules.max_ply().unwrap_or(GeisterRules::DEFAULT_MAX_PLY)),
+    };
+    Ok(rules.with_max_ply(max_ply))
+}
+
+/// コマンドライン引数のうち，`--name value`の形で指定されたオプションの値を返す．
+fn option_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    args.next()
+}
+
+/// コマンドライン引数のうち，`--name value`の形で指定されたオプションの値を解釈する．
+fn parse_option<T>(name: &str) -> Result<Option<T>, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    option_value(name)
+        .map(|s| {
+            s.parse::<T>()
+                .map_err(|e| format!("Invalid {}: {}", name, e))
+        })
+        .transpose()
+}
+
 fn main() {
-    let strategy = PlayerStrategy {
+    // サブコマンドは対局の設定を使わないため，対局の設定より先に判別する
+    let subcommand = std::env::args().nth(1);
+
+    // `bench`を指定すると，対局の代わりにランダムプレイアウトの速度を測る
+    if subcommand.as_deref() == Some("bench") {
+        let (rules, playout_count) = match rules_from_args().and_then(|rules| {
+            let playout_count = parse_option::<usize>("--playouts")?.unwrap_or(1000);
+            Ok((rules, playout_count))
+        }) {
+            Ok(benchmark_options) => benchmark_options,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+        run_benchmark(rules, playout_count, &RandomPlacement);
+        return;
+    }
+
+    // `load game.txt`を指定すると，対局の代わりに棋譜を読み込んで表示する
+    // `replay game.txt`を指定すると，対局の代わりに棋譜を1手ずつ再生する
+    if matches!(subcommand.as_deref(), Some("load") | Some("replay")) {
+        let record = match std::env::args()
+            .nth(2)
+            .ok_or_else(|| "Usage: load|replay <file>".to_string())
+            .and_then(GameRecord::load)
+        {
+            Ok(record) => record,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+        if subcommand.as_deref() == Some("replay") {
+            let _alternate_screen = AlternateScreen::enter();
+            replay(&record, &KeyboardInput::new());
+        } else {
+            println!("{}", record);
+            println!(
+                "{}",
+                write_state_for(record.current_state(), None, record.actions().last()).unwrap()
+            );
+        }
+        return;
+    }
+
+    let options = match GameOptions::from_args() {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let rules = options.rules;
+
+    let player_strategy = PlayerStrategy {
         keyboard_input: KeyboardInput::new(),
+        hot_seat: options.is_hot_seat(),
     };
-    let mut current_state = GeisterState::create_initial_state(select_initial_positions());
-    let mut current_actor = Actor::First;
+    // 同じ種を指定すれば同じ対局を再現できるように，乱数はすべてこの種から生成する
+    println!("Seed: {}", options.seed);
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    // 対局中の表示は代替画面で行い，対局後にスクロールして配置や盤面をさかのぼれないようにする
+    let alternate_screen = AlternateScreen::enter();
+    let mut setup_of = |actor| {
+        let placement: &dyn InitialPlacement = match options.setup_policy_of(actor) {
+            SetupPolicy::Edit => &player_strategy,
+            SetupPolicy::Random => &RandomPlacement,
+            SetupPolicy::HolyFront => &HolyFrontPlacement,
+            SetupPolicy::HolyFlank => &HolyFlankPlacement,
+            SetupPolicy::WeightedRandom(placement) => placement,
+        };
+        placement.select_setup(&rules, actor, &mut rng)
+    };
+    let first_setup = setup_of(Actor::First);
+    let second_setup = setup_of(Actor::Second);
+    let initial_state = match GeisterState::create_initial_state(rules, &first_setup, &second_setup)
+    {
+        Ok(state) => state,
+        Err(e) => {
+            drop(alternate_screen);
+            println!("{}", e);
+            return;
+        }
+    };
+    // 行動はすべて棋譜に記録し，現在の状態も棋譜から得る
+    let mut record = GameRecord::new(initial_state);
+    record.set_tags(RecordTags {
+        first: Some(options.player_description_of(Actor::First)),
+        second: Some(options.player_description_of(Actor::Second)),
+        seed: Some(options.seed),
+        date: Some(today()),
+    });
+    let computer_strategy = ComputerStrategy::new(options.depth, rng.gen());
+    // 直前の操作の結果など，次に盤面を表示するときに添える知らせ
+    let mut notice = None;
+    // 端末の前にいるプレイヤー．人間同士の対局では，これが変わるたびに端末を渡してもらう
+    let mut seated_actor = None;
+
+    let outcome = loop {
+        let current_state = record.current_state();
+        let current_actor = current_state.current_actor();
+        if let Some(outcome) = GeisterRule::outcome_of(current_state) {
+            break outcome;
+        }
 
-    while !GeisterRule::is_game_over(&current_state) {
         // 相手プレイヤーの情報が見えないように，端末の表示内容をクリア
-        print!("\x1B[2J");
-        // 現在の状態を表示
-        println!("{:?}'s turn", current_actor);
-        match write_state_for(&current_state, Some(current_actor)) {
-            Ok(s) => println!("{}", s),
-            Err(e) => {
-                println!("An error was occurred during writing field: {}", e);
-                break;
+        let viewpoint = options.viewpoint_on_turn_of(current_actor);
+        if options.is_hot_seat() && seated_actor != viewpoint {
+            hand_off_to(current_actor, &player_strategy.keyboard_input);
+            seated_actor = viewpoint;
+        }
+        // 直前の行動とその結果を，操作の結果などの知らせと合わせて盤面に添える
+        let last_action = record.actions().last();
+        let mut notice_lines = write_recent_events(&record, viewpoint);
+        notice_lines.extend(notice.take());
+        let turn_notice = Some(notice_lines.join("\n")).filter(|s| !s.is_empty());
+
+        // 現在の状態を表示．人間の手番では，カーソルを添えた盤面を`PlayerStrategy`が表示する
+        if options.player_kind_of(current_actor) == PlayerKind::Computer {
+            clear_screen();
+            println!("{:?}'s turn", current_actor);
+            match write_state_for(current_state, viewpoint, last_action) {
+                Ok(s) => println!("{}", s),
+                Err(e) => {
+                    drop(alternate_screen);
+                    println!("An error was occurred during writing field: {}", e);
+                    return;
+                }
+            }
+            if let Some(turn_notice) = &turn_notice {
+                println!("{}", turn_notice);
             }
         }
 
-        // 行動選択
-        let action = strategy
-            .select_action(&current_state, current_actor)
-            .expect("At least 1 action must be available");
-        // 状態遷移とターンプレイヤー交代
-        current_state = GeisterRule::translate_state(&current_state, &action);
-        current_actor = current_actor.opponent();
+        // 行動選択．相手の👻の種類を知られないよう，観測できる情報だけを渡す．
+        // 行動が選ばれなかった場合は投了とみなす．
+        let view = PlayerView::new(current_state, current_actor);
+        let command = match options.player_kind_of(current_actor) {
+            PlayerKind::Human => player_strategy.select_command(
+                &view,
+                current_actor,
+                last_action,
+                turn_notice.as_deref(),
+            ),
+            PlayerKind::Computer => {
+                let action = computer_strategy.select_action(&view, current_actor);
+                // コンピュータ同士の対局を観戦できるよう，少し待つ
+                if options.viewpoint_on_turn_of(current_actor).is_none() {
+                    thread::sleep(Duration::from_millis(500));
+                }
+                match action {
+                    Some(action) => PlayerCommand::Act(action),
+                    None => PlayerCommand::Resign,
+                }
+            }
+        };
+        let action = match command {
+            PlayerCommand::Act(action) => action,
+            PlayerCommand::Undo => {
+                notice = Some(undo_last_action_of(
+                    &mut record,
+                    current_actor,
+                    &options,
+                    &player_strategy.keyboard_input,
+                ));
+                continue;
+            }
+            PlayerCommand::Resign => {
+                break GameOutcome::Win {
+                    winner: current_actor.opponent(),
+                    reason: OutcomeReason::Resignation,
+                }
+            }
+        };
+        // ルール上とれない行動を選んだプレイヤーは投了したものとみなす
+        if let Err(e) = record.push(action) {
+            notice = Some(format!(
+                "{:?} chose an illegal action: {}",
+                current_actor, e
+            ));
+            break GameOutcome::Win {
+                winner: current_actor.opponent(),
+                reason: OutcomeReason::Resignation,
+            };
+        }
 
         println!();
+    };
+
+    record.set_outcome(outcome);
+    // ゲーム結果は元の画面に表示し，対局後も見えるようにする
+    drop(alternate_screen);
+    if let Some(notice) = notice {
+        println!("{}", notice);
     }
 
-    // ゲーム勝者を表示
+    // ゲーム結果と，対局を再現するための乱数の種を表示
+    println!("{}", outcome);
+    println!("Seed: {}", options.seed);
     println!(
-        "The winner is {:?}",
-        GeisterRule::winner_of(&current_state).expect("The winner must be determined")
+        "{}",
+        write_state_for(record.current_state(), None, record.actions().last()).unwrap()
     );
-    println!("{}", write_state_for(&current_state, None).unwrap());
+
+    if let Some(save_path) = options.save_path {
+        match record.save(&save_path) {
+            Ok(()) => println!("Saved the game record to {}", save_path),
+            Err(e) => println!("{}", e),
+        }
+    }
 }
@@ -0,0 +1,857 @@
+use crate::{
+    geisters, Geister, GeisterAction, GeisterMovement, GeisterRule, GeisterRules, GeisterState,
+    IllegalAction, ObservedGeister, OwnedGeister, PlayerView,
+};
+use data_structure::{Pair, Table, TableIndex, TableSize};
+use minimax_strategy::{actors, Actor};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// 局面を1行で表す表記．`GeisterState`と`PlayerView`の`Display`・`FromStr`で共通して使う．
+///
+/// `1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4`のように，空白で区切った次の要素を並べる．
+/// 1. フィールド．`y = 0`の行から順に`/`で区切って並べ，各行は`x = 0`のマスから順に並べる．
+///    先手の善良な👻・邪悪な👻・種類の分からない👻を`H`・`E`・`U`，後手の👻を`h`・`e`・`u`で表し，
+///    連続する空きマスはその数で表す．
+/// 1. 手番のプレイヤー．先手は`f`，後手は`s`．
+/// 1. 取り除かれた👻の数．先手の善良な👻・先手の邪悪な👻・後手の善良な👻・後手の邪悪な👻の順に`,`で区切る．
+/// 1. フィールドから上がった👻の所有者．`f`，`s`，またはいなければ`-`．
+/// 1. ゲーム開始から進んだ手数．
+/// 1. ルール．`GeisterRules`の表記を使う．
+/// 1. (`PlayerView`のみ) 観測者．`@f`または`@s`．
+struct Notation {
+    /// このゲームのルール．
+    rules: GeisterRules,
+    /// フィールドに存在する👻．
+    lattices: Table<Option<ObservedGeister>>,
+    /// フィールドから取り除かれた👻の数．
+    killed_geister_counts: HashMap<OwnedGeister, usize>,
+    /// フィールドから上がった👻の所有者．
+    actor_of_cleared_geister: Option<Actor>,
+    /// ゲーム開始から進んだ手数．
+    ply: usize,
+    /// 観測者．`GeisterState`の表記では`None`となる．
+    viewpoint: Option<Actor>,
+}
+
+impl Notation {
+    /// 手番のプレイヤーを返す．
+    fn current_actor(&self) -> Actor {
+        GeisterRule::actor_on_ply(self.ply)
+    }
+
+    /// フィールドの👻と取り除かれた👻の数が，ルールの👻の数を超えていないか確かめる．
+    fn check_geister_counts(&self) -> Result<(), String> {
+        let size = self.rules.field_size();
+        for &actor in actors().iter() {
+            let observed_geisters = (0..size.y)
+                .flat_map(|y| (0..size.x).map(move |x| TableIndex::new(x, y)))
+                .filter_map(|position| self.lattices[position])
+                .filter(|observed_geister| observed_geister.owner == actor)
+                .collect::<Vec<_>>();
+
+            let mut total_killed_count = 0;
+            let mut total_initial_count = 0;
+            for &geister in geisters().iter() {
+                let killed_geister_count =
+                    self.killed_geister_counts[&OwnedGeister::new(geister, actor)];
+                let count = observed_geisters
+                    .iter()
+                    .filter(|observed_geister| observed_geister.geister == Some(geister))
+                    .count()
+                    + killed_geister_count;
+                if count > self.rules.initial_geister_count(geister) {
+                    return Err(format!("Too many {:?} geisters of {:?}", geister, actor));
+                }
+                total_killed_count += killed_geister_count;
+                total_initial_count += self.rules.initial_geister_count(geister);
+            }
+            if total_killed_count + observed_geisters.len() > total_initial_count {
+                return Err(format!("Too many geisters of {:?}", actor));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Notation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // フィールド
+        let size = self.rules.field_size();
+        for y in 0..size.y {
+            if y > 0 {
+                write!(f, "/")?;
+            }
+            let mut empty_count = 0;
+            for x in 0..size.x {
+                match self.lattices[TableIndex::new(x, y)] {
+                    Some(observed_geister) => {
+                        if empty_count > 0 {
+                            write!(f, "{}", empty_count)?;
+                            empty_count = 0;
+                        }
+                        write!(f, "{}", char_of_geister(observed_geister))?;
+                    }
+                    None => empty_count += 1,
+                }
+            }
+            if empty_count > 0 {
+                write!(f, "{}", empty_count)?;
+            }
+        }
+
+        // 手番，取り除かれた👻の数，上がった👻の所有者，手数，ルール
+        write!(f, " {}", char_of_actor(self.current_actor()))?;
+        let killed_geister_counts = actors()
+            .iter()
+            .flat_map(|&actor| {
+                [Geister::Holy, Geister::Evil]
+                    .iter()
+                    .map(move |&geister| OwnedGeister::new(geister, actor))
+            })
+            .map(|owned_geister| self.killed_geister_counts[&owned_geister].to_string())
+            .collect::<Vec<_>>();
+        write!(f, " {}", killed_geister_counts.join(","))?;
+        match self.actor_of_cleared_geister {
+            Some(actor) => write!(f, " {}", char_of_actor(actor))?,
+            None => write!(f, " -")?,
+        }
+        write!(f, " {} {}", self.ply, self.rules)?;
+
+        // 観測者
+        if let Some(viewpoint) = self.viewpoint {
+            write!(f, " @{}", char_of_actor(viewpoint))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Notation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let (board, side, captures, cleared, ply, rules) = match (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) {
+            (Some(board), Some(side), Some(captures), Some(cleared), Some(ply), Some(rules)) => {
+                (board, side, captures, cleared, ply, rules)
+            }
+            _ => return Err(format!("Too few fields: {}", s)),
+        };
+        let viewpoint = match fields.next() {
+            Some(viewpoint) if viewpoint.starts_with('@') => Some(actor_of_str(&viewpoint[1..])?),
+            Some(viewpoint) => return Err(format!("Invalid viewpoint: {}", viewpoint)),
+            None => None,
+        };
+        if fields.next().is_some() {
+            return Err(format!("Too many fields: {}", s));
+        }
+
+        let rules = rules.parse::<GeisterRules>()?;
+        let size = rules.field_size();
+
+        // フィールド
+        let ranks = board.split('/').collect::<Vec<_>>();
+        if ranks.len() != size.y {
+            return Err(format!("The field must have {} ranks: {}", size.y, board));
+        }
+        let mut lattices = Table::from_fill(None, size);
+        for (y, rank) in ranks.into_iter().enumerate() {
+            let mut x = 0usize;
+            let mut empty_count = String::new();
+            // 末尾に番兵を付けて，行末の空きマスの数も読み取る
+            for c in rank.chars().chain(Some('/')) {
+                if c.is_ascii_digit() {
+                    empty_count.push(c);
+                    continue;
+                }
+                if !empty_count.is_empty() {
+                    x = empty_count
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|count| x.checked_add(count))
+                        .ok_or_else(|| format!("Invalid rank: {}", rank))?;
+                    empty_count.clear();
+                }
+                if c == '/' {
+                    break;
+                }
+                let observed_geister =
+                    geister_of_char(c).ok_or_else(|| format!("Invalid geister: {}", c))?;
+                if x >= size.x {
+                    return Err(format!("Rank is too long: {}", rank));
+                }
+                lattices[TableIndex::new(x, y)] = Some(observed_geister);
+                x += 1;
+            }
+            if x != size.x {
+                return Err(format!("Rank must have {} squares: {}", size.x, rank));
+            }
+        }
+
+        // 取り除かれた👻の数
+        let mut counts = captures.split(',').map(|count| count.parse::<usize>());
+        let mut killed_geister_counts = HashMap::new();
+        for &actor in actors().iter() {
+            for &geister in [Geister::Holy, Geister::Evil].iter() {
+                match counts.next() {
+                    Some(Ok(count)) => {
+                        killed_geister_counts.insert(OwnedGeister::new(geister, actor), count);
+                    }
+                    _ => return Err(format!("Invalid captures: {}", captures)),
+                }
+            }
+        }
+        if counts.next().is_some() {
+            return Err(format!("Invalid captures: {}", captures));
+        }
+
+        let actor_of_cleared_geister = match cleared {
+            "-" => None,
+            _ => Some(actor_of_str(cleared)?),
+        };
+        let ply = ply
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid ply: {}", ply))?;
+
+        let notation = Self {
+            rules,
+            lattices,
+            killed_geister_counts,
+            actor_of_cleared_geister,
+            ply,
+            viewpoint,
+        };
+        // 手番は手数から決まるため，両者が矛盾していないか確かめる
+        if actor_of_str(side)? != notation.current_actor() {
+            return Err(format!(
+                "{:?} cannot be on move at ply {}",
+                actor_of_str(side)?,
+                ply
+            ));
+        }
+        notation.check_geister_counts()?;
+        Ok(notation)
+    }
+}
+
+/// 行動の表記．
+/// マスは`b2`のように列を表す英小文字と行を表す数字で表し，次のいずれかの形で行動を表す．
+/// - `b2-b3` `b2`の👻を隣の`b3`へ動かす．
+/// - `b2N` `b2`の👻を`N`(行の番号が増える向き)，`S`，`E`(列の英字が進む向き)，`W`のいずれかへ動かす．
+/// - `a1x` `a1`の👻をフィールドから上がらせる．
+///
+/// 列と行は，ある視点プレイヤーから見て左の列を`a`，自分側の最奥の行を`1`として数える．
+/// 先手から見た表記を絶対表記，行動するプレイヤーから見た表記を相対表記と呼ぶ．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionNotation {
+    /// 動かす👻のマス．`x`は列，`y`は行を表し，どちらも0から数える．
+    pub position: TableIndex,
+    /// 👻の動作．移動の向きも，視点プレイヤーから見た列・行の増減で表す．
+    pub movement: GeisterMovement,
+}
+
+impl ActionNotation {
+    /// 指定した行動を，指定した視点プレイヤーから見て表記する．
+    pub fn of(action: &GeisterAction, field_size: TableSize, viewpoint: Actor) -> Self {
+        Self {
+            position: turn_square(action.target_geister_position, field_size, viewpoint),
+            movement: turn_movement(action.geister_movement, viewpoint),
+        }
+    }
+
+    /// 指定した視点プレイヤーから見た表記として，指定したプレイヤーの行動に戻す．
+    /// マスがフィールドの外にある場合はエラーを返す．
+    pub fn to_action(
+        self,
+        field_size: TableSize,
+        viewpoint: Actor,
+        actor: Actor,
+    ) -> Result<GeisterAction, IllegalAction> {
+        let p = self.position;
+        if p.x >= field_size.x || p.y >= field_size.y {
+            return Err(IllegalAction::OffBoard);
+        }
+        Ok(GeisterAction::new(
+            turn_square(p, field_size, viewpoint),
+            turn_movement(self.movement, viewpoint),
+            actor,
+        ))
+    }
+
+    /// 観測者から見た相対表記を，観測者の行動として読み取る．
+    /// 表記が不正な場合や，ルール上とれない行動の場合はその理由を返す．
+    pub fn parse_action_for(s: &str, view: &PlayerView) -> Result<GeisterAction, String> {
+        let action = s.parse::<ActionNotation>()?.to_action(
+            view.rules().field_size(),
+            view.viewpoint(),
+            view.viewpoint(),
+        );
+        let action = action.map_err(|e| e.to_string())?;
+        view.validate(&action).map_err(|e| e.to_string())?;
+        Ok(action)
+    }
+
+    /// 指定したマスを，指定した視点プレイヤーから見た`b2`のような表記で返す．
+    pub fn square_of(position: TableIndex, field_size: TableSize, viewpoint: Actor) -> String {
+        square_name(turn_square(position, field_size, viewpoint))
+    }
+}
+
+impl Display for ActionNotation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_square(f, self.position)?;
+        match self.movement {
+            GeisterMovement::Direction(d) => {
+                let destination = self
+                    .position
+                    .try_cast::<isize>()
+                    .ok()
+                    .and_then(|p| (p + d).try_cast::<usize>().ok());
+                match destination {
+                    Some(destination) => {
+                        write!(f, "-")?;
+                        write_square(f, destination)
+                    }
+                    // 行き先を表記できない場合は向きで表す
+                    None => write!(f, "{}", char_of_direction(d)),
+                }
+            }
+            GeisterMovement::Clear => write!(f, "x"),
+        }
+    }
+}
+
+impl FromStr for ActionNotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid action: {}", s);
+        let (position, rest) = parse_square(s).ok_or_else(invalid)?;
+        let movement = match rest {
+            "x" => GeisterMovement::Clear,
+            _ if rest.starts_with('-') => {
+                let destination = match parse_square(&rest[1..]) {
+                    Some((destination, "")) => destination,
+                    _ => return Err(invalid()),
+                };
+                let d = destination.try_cast::<isize>().map_err(|_| invalid())?
+                    - position.try_cast::<isize>().map_err(|_| invalid())?;
+                if !matches!((d.x, d.y), (0, 1) | (0, -1) | (1, 0) | (-1, 0)) {
+                    return Err(format!("Geisters move only to adjacent squares: {}", s));
+                }
+                GeisterMovement::Direction(d)
+            }
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next().and_then(direction_of_char), chars.next()) {
+                    (Some(d), None) => GeisterMovement::Direction(d),
+                    _ => return Err(invalid()),
+                }
+            }
+        };
+        Ok(Self { position, movement })
+    }
+}
+
+impl Display for GeisterState {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let size = self.rules().field_size();
+        let mut lattices = Table::from_fill(None, size);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let position = TableIndex::new(x, y);
+                lattices[position] = self.geister_at(position).map(observe_all);
+            }
+        }
+
+        Notation {
+            rules: *self.rules(),
+            lattices,
+            killed_geister_counts: killed_geister_counts_of(|owned_geister| {
+                self.killed_geister_count(owned_geister)
+            }),
+            actor_of_cleared_geister: self.actor_of_cleared_geister(),
+            ply: self.ply(),
+            viewpoint: None,
+        }
+        .fmt(f)
+    }
+}
+
+/// 種類の分からない👻や観測者を含まない表記から状態を生成する．
+impl FromStr for GeisterState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let notation = s.parse::<Notation>()?;
+        if notation.viewpoint.is_some() {
+            return Err("A state cannot have a viewpoint".to_string());
+        }
+
+        let size = notation.rules.field_size();
+        let mut lattices = Table::from_fill(None, size);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let position = TableIndex::new(x, y);
+                lattices[position] = match notation.lattices[position] {
+                    Some(ObservedGeister {
+                        owner,
+                        geister: Some(geister),
+                    }) => Some(OwnedGeister::new(geister, owner)),
+                    Some(_) => return Err("A state cannot have unknown geisters".to_string()),
+                    None => None,
+                };
+            }
+        }
+
+        Ok(GeisterState::from_parts(
+            notation.rules,
+            lattices,
+            notation.killed_geister_counts,
+            notation.actor_of_cleared_geister,
+            notation.ply,
+        ))
+    }
+}
+
+impl Display for PlayerView {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Notation {
+            rules: *self.rules(),
+            lattices: self.lattices.clone(),
+            killed_geister_counts: killed_geister_counts_of(|owned_geister| {
+                self.killed_geister_count(owned_geister)
+            }),
+            actor_of_cleared_geister: self.actor_of_cleared_geister,
+            ply: self.ply(),
+            viewpoint: Some(self.viewpoint()),
+        }
+        .fmt(f)
+    }
+}
+
+/// 観測者を含む表記から観測結果を生成する．
+/// 観測者の👻は種類が分かっており，相手の👻は種類が分からない(`U`または`u`)必要がある．
+impl FromStr for PlayerView {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let notation = s.parse::<Notation>()?;
+        let viewpoint = notation
+            .viewpoint
+            .ok_or_else(|| "A view must have a viewpoint".to_string())?;
+
+        let size = notation.rules.field_size();
+        for y in 0..size.y {
+            for x in 0..size.x {
+                if let Some(observed_geister) = notation.lattices[TableIndex::new(x, y)] {
+                    let is_own_geister = observed_geister.owner == viewpoint;
+                    if is_own_geister != observed_geister.geister.is_some() {
+                        return Err(format!(
+                            "Only the geisters of {:?} must be known in its view",
+                            viewpoint
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(PlayerView::from_parts(
+            notation.rules,
+            viewpoint,
+            notation.lattices,
+            notation.killed_geister_counts,
+            notation.actor_of_cleared_geister,
+            notation.ply,
+        ))
+    }
+}
+
+/// 種類を含めて，指定した👻を観測した結果を返す．
+fn observe_all(owned_geister: OwnedGeister) -> ObservedGeister {
+    ObservedGeister {
+        owner: owned_geister.owner,
+        geister: Some(owned_geister.geister),
+    }
+}
+
+/// 指定した関数で，所有者・種類ごとに取り除かれた👻の数を求める．
+fn killed_geister_counts_of<F>(killed_geister_count: F) -> HashMap<OwnedGeister, usize>
+where
+    F: Fn(OwnedGeister) -> usize,
+{
+    let mut killed_geister_counts = HashMap::new();
+    for &actor in actors().iter() {
+        for &geister in geisters().iter() {
+            let owned_geister = OwnedGeister::new(geister, actor);
+            killed_geister_counts.insert(owned_geister, killed_geister_count(owned_geister));
+        }
+    }
+    killed_geister_counts
+}
+
+/// 👻を表す文字を返す．
+fn char_of_geister(observed_geister: ObservedGeister) -> char {
+    let c = match observed_geister.geister {
+        Some(Geister::Holy) => 'h',
+        Some(Geister::Evil) => 'e',
+        None => 'u',
+    };
+    match observed_geister.owner {
+        Actor::First => c.to_ascii_uppercase(),
+        Actor::Second => c,
+    }
+}
+
+/// 文字が表す👻`geister`を`Some(geister)`として返す．
+/// 👻を表す文字でなければ`None`を返す．
+fn geister_of_char(c: char) -> Option<ObservedGeister> {
+    let geister = match c.to_ascii_lowercase() {
+        'h' => Some(Geister::Holy),
+        'e' => Some(Geister::Evil),
+        'u' => None,
+        _ => return None,
+    };
+    let owner = if c.is_ascii_uppercase() {
+        Actor::First
+    } else {
+        Actor::Second
+    };
+    Some(ObservedGeister { owner, geister })
+}
+
+/// プレイヤーを表す文字を返す．
+fn char_of_actor(actor: Actor) -> char {
+    match actor {
+        Actor::First => 'f',
+        Actor::Second => 's',
+    }
+}
+
+/// 文字列が表すプレイヤーを返す．
+fn actor_of_str(s: &str) -> Result<Actor, String> {
+    match s {
+        "f" => Ok(Actor::First),
+        "s" => Ok(Actor::Second),
+        _ => Err(format!("Invalid actor: {}", s)),
+    }
+}
+
+/// 盤面上の向きと，視点プレイヤーから見た列・行の増減とを相互に変換する．
+/// 先手から見ると行の向きが，後手から見ると列の向きが逆になる．
+fn turn_movement(movement: GeisterMovement, viewpoint: Actor) -> GeisterMovement {
+    match movement {
+        GeisterMovement::Direction(d) => GeisterMovement::Direction(match viewpoint {
+            Actor::First => Pair::new(d.x, -d.y),
+            Actor::Second => Pair::new(-d.x, d.y),
+        }),
+        GeisterMovement::Clear => GeisterMovement::Clear,
+    }
+}
+
+/// フィールド上のマスと，視点プレイヤーから見た列・行とを相互に変換する．
+/// 先手から見ると行の順が，後手から見ると列の順が逆になる．
+fn turn_square(position: TableIndex, field_size: TableSize, viewpoint: Actor) -> TableIndex {
+    match viewpoint {
+        Actor::First => TableIndex::new(position.x, field_size.y - 1 - position.y),
+        Actor::Second => TableIndex::new(field_size.x - 1 - position.x, position.y),
+    }
+}
+
+/// `b2`のようなマスの表記を返す．
+fn square_name(position: TableIndex) -> String {
+    let file = std::char::from_u32('a' as u32 + position.x as u32).unwrap_or('?');
+    format!("{}{}", file, position.y + 1)
+}
+
+/// `b2`のようにマスを書き込む．
+fn write_square(f: &mut Formatter, position: TableIndex) -> fmt::Result {
+    write!(f, "{}", square_name(position))
+}
+
+/// 文字列の先頭にある`b2`のようなマスを読み取り，マスと残りの文字列を返す．
+fn parse_square(s: &str) -> Option<(TableIndex, &str)> {
+    let file = s.chars().next().filter(char::is_ascii_lowercase)?;
+    let rank_length = s[1..].chars().take_while(char::is_ascii_digit).count();
+    let rank = s[1..1 + rank_length]
+        .parse::<usize>()
+        .ok()?
+        .checked_sub(1)?;
+    let x = file as usize - 'a' as usize;
+    Some((TableIndex::new(x, rank), &s[1 + rank_length..]))
+}
+
+/// 列・行の増減を表す`N`，`S`，`E`，`W`の文字を返す．
+fn char_of_direction(direction: Pair<isize>) -> char {
+    match (direction.x, direction.y) {
+        (0, 1) => 'N',
+        (0, -1) => 'S',
+        (1, 0) => 'E',
+        (-1, 0) => 'W',
+        _ => '?',
+    }
+}
+
+/// `N`，`S`，`E`，`W`の文字が表す列・行の増減を返す．
+fn direction_of_char(c: char) -> Option<Pair<isize>> {
+    match c {
+        'N' => Some(Pair::new(0, 1)),
+        'S' => Some(Pair::new(0, -1)),
+        'E' => Some(Pair::new(1, 0)),
+        'W' => Some(Pair::new(-1, 0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InitialPlacement, RandomPlacement};
+    use minimax_strategy::Rule;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    /// 4x4，6x6，8x8のフィールドで，ランダムな対局に現れる状態をすべて返す．
+    fn states_of_random_games(seed: u64) -> Vec<GeisterState> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut states = vec![];
+        for &size in [4, 6, 8].iter() {
+            let rules = GeisterRules::with_field_size(TableSize::new(size, size))
+                .unwrap()
+                .with_max_ply(Some(200));
+            for _ in 0..5 {
+                let first_setup = RandomPlacement.select_setup(&rules, Actor::First, &mut rng);
+                let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, &mut rng);
+                let mut state =
+                    GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+                states.push(state.clone());
+                while !GeisterRule::is_game_over(&state) {
+                    let actions =
+                        GeisterRule::iterate_available_actions(&state, state.current_actor());
+                    GeisterRule::apply(&mut state, actions.choose(&mut rng).unwrap());
+                    states.push(state.clone());
+                }
+            }
+        }
+        states
+    }
+
+    #[test]
+    fn state_notation_round_trip() {
+        for state in states_of_random_games(0) {
+            let s = state.to_string();
+            let parsed = s.parse::<GeisterState>().unwrap();
+            assert_eq!(state, parsed);
+            assert_eq!(state.ply(), parsed.ply());
+            assert_eq!(s, parsed.to_string());
+        }
+    }
+
+    #[test]
+    fn view_notation_round_trip() {
+        for state in states_of_random_games(1) {
+            for &viewpoint in actors().iter() {
+                let s = PlayerView::new(&state, viewpoint).to_string();
+                assert!(s.ends_with(&format!(" @{}", char_of_actor(viewpoint))));
+                let parsed = s.parse::<PlayerView>().unwrap();
+                assert_eq!(viewpoint, parsed.viewpoint());
+                assert_eq!(s, parsed.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn view_notation_has_unknown_geisters() {
+        let s = "1uuuu1/1uuuu1/6/6/1HEHE1/1EHEH1 f 0,0,0,0 - 0 6x6:4:4 @f";
+        let view = s.parse::<PlayerView>().unwrap();
+        assert_eq!(
+            Some(ObservedGeister {
+                owner: Actor::Second,
+                geister: None,
+            }),
+            view.lattices[TableIndex::new(1, 0)]
+        );
+        assert_eq!(s, view.to_string());
+        // 状態には種類の分からない👻を含められない
+        assert!(s.trim_end_matches(" @f").parse::<GeisterState>().is_err());
+    }
+
+    #[test]
+    fn reject_invalid_notations() {
+        let invalid_states = [
+            "",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 extra",
+            "1hhhh1/1eeee1/6/6/1HHHH1 f 0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1/6 f 0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/7/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/5/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEEE f 0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEx1 f 0,0,0,0 - 0 6x6:4:4",
+            "H18446744073709551615/6/6/6/6/6 f 0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 s 0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 1,0,0,0 - 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 x 0 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - -1 6x6:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x7:4:4",
+            "1hhhh1/1eeee1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 @f",
+            "1uuuu1/1uuuu1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4",
+            "1hh1/4/4/1HH1 f 0,0,0,0 - 0 6x6:4:4",
+        ];
+        for s in invalid_states.iter() {
+            assert!(s.parse::<GeisterState>().is_err(), "{}", s);
+        }
+
+        let invalid_views = [
+            "1uuuu1/1uuuu1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4",
+            "1uuuu1/1uuuu1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 f",
+            "1uuuu1/1uuuu1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 @x",
+            "1uuuu1/1uuuu1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 @s",
+            "1hhhh1/1uuuu1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 @f",
+            "1uuuu1/1uuuu1/6/6/1UUUU1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 @f",
+            "uuuuu1/1uuuu1/6/6/1HHHH1/1EEEE1 f 0,0,0,0 - 0 6x6:4:4 @f",
+            "H18446744073709551615/6/6/6/6/6 f 0,0,0,0 - 0 6x6:4:4 @f",
+        ];
+        for s in invalid_views.iter() {
+            assert!(s.parse::<PlayerView>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn action_notation_round_trip() {
+        for state in states_of_random_games(2) {
+            let field_size = state.rules().field_size();
+            let actor = state.current_actor();
+            for action in GeisterRule::iterate_available_actions(&state, actor) {
+                for &viewpoint in actors().iter() {
+                    let notation = ActionNotation::of(&action, field_size, viewpoint);
+                    let parsed = notation.to_string().parse::<ActionNotation>().unwrap();
+                    assert_eq!(notation, parsed);
+                    assert_eq!(Ok(action), parsed.to_action(field_size, viewpoint, actor));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn notation_round_trip_at_max_field_size() {
+        let length = GeisterRules::MAX_FIELD_LENGTH;
+        let field_size = TableSize::new(length, length);
+        let rules = GeisterRules::with_field_size(field_size)
+            .unwrap()
+            .with_max_ply(Some(100));
+        let mut rng = StdRng::seed_from_u64(3);
+        let first_setup = RandomPlacement.select_setup(&rules, Actor::First, &mut rng);
+        let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, &mut rng);
+        let mut state =
+            GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+        loop {
+            let s = state.to_string();
+            assert_eq!(state, s.parse::<GeisterState>().unwrap());
+            if GeisterRule::is_game_over(&state) {
+                break;
+            }
+            let actor = state.current_actor();
+            let actions = GeisterRule::iterate_available_actions(&state, actor);
+            for action in actions.iter() {
+                for &viewpoint in actors().iter() {
+                    let notation = ActionNotation::of(action, field_size, viewpoint);
+                    let parsed = notation.to_string().parse::<ActionNotation>().unwrap();
+                    assert_eq!(Ok(*action), parsed.to_action(field_size, viewpoint, actor));
+                }
+            }
+            GeisterRule::apply(&mut state, actions.choose(&mut rng).unwrap());
+        }
+
+        // 最も右の列は`z`で表す
+        let corner = TableIndex::new(length - 1, 0);
+        assert_eq!(
+            "z26",
+            ActionNotation::square_of(corner, field_size, Actor::First)
+        );
+        assert_eq!(
+            "a1",
+            ActionNotation::square_of(corner, field_size, Actor::Second)
+        );
+        let clear = "z26x".parse::<ActionNotation>().unwrap();
+        assert_eq!(
+            Ok(GeisterAction::new(
+                corner,
+                GeisterMovement::Clear,
+                Actor::First
+            )),
+            clear.to_action(field_size, Actor::First, Actor::First)
+        );
+    }
+
+    #[test]
+    fn action_notation_forms() {
+        let field_size = TableSize::new(6, 6);
+        let parse = |s: &str| s.parse::<ActionNotation>().unwrap();
+        // 向きの表記は，行き先のマスの表記と同じ行動を表す
+        assert_eq!(parse("b2-b3"), parse("b2N"));
+        assert_eq!(parse("b2-b1"), parse("b2S"));
+        assert_eq!(parse("b2-c2"), parse("b2E"));
+        assert_eq!(parse("b2-a2"), parse("b2W"));
+        // 行き先を表記できない場合だけ向きで表す
+        assert_eq!("a1S", parse("a1S").to_string());
+        assert_eq!("a1W", parse("a1W").to_string());
+        assert_eq!("b2-b3", parse("b2N").to_string());
+        assert_eq!("a1x", parse("a1x").to_string());
+        assert_eq!(GeisterMovement::Clear, parse("f6x").movement);
+
+        // 後手から見た表記は，先手から見た表記を180度回したものとなる
+        let action = GeisterAction::new(
+            TableIndex::new(0, 0),
+            GeisterMovement::Direction(Pair::new(0, 1)),
+            Actor::Second,
+        );
+        assert_eq!(
+            "a6-a5",
+            ActionNotation::of(&action, field_size, Actor::First).to_string()
+        );
+        assert_eq!(
+            "f1-f2",
+            ActionNotation::of(&action, field_size, Actor::Second).to_string()
+        );
+        assert_eq!(
+            Err(IllegalAction::OffBoard),
+            parse("g1N").to_action(field_size, Actor::First, Actor::First)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_action_notations() {
+        let invalid_actions = [
+            "",
+            "a1",
+            "A1x",
+            "a0x",
+            "a1xx",
+            "a1Q",
+            "a1NN",
+            "a1-",
+            "a1-a1",
+            "a1-a3",
+            "a1-b2",
+            "a1-a2x",
+            "z1-a9223372036854775808",
+            "a9223372036854775808-z1",
+            "a18446744073709551616x",
+        ];
+        for s in invalid_actions.iter() {
+            assert!(s.parse::<ActionNotation>().is_err(), "{}", s);
+        }
+    }
+}
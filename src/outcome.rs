@@ -0,0 +1,162 @@
+use minimax_strategy::Actor;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// ゲームが終了した理由を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutcomeReason {
+    /// 善良な👻がフィールドから上がった．
+    Escape,
+    /// 相手の善良な👻をすべて取り除いた．
+    CapturedAllHoly,
+    /// 自分の邪悪な👻をすべて相手に取らせた．
+    LostAllEvil,
+    /// 手数の上限に達した．
+    MoveLimit,
+    /// 一方のプレイヤーが投了した．
+    Resignation,
+}
+
+/// ゲームの結果を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameOutcome {
+    /// 勝者が決定した．
+    Win {
+        winner: Actor,
+        reason: OutcomeReason,
+    },
+    /// 引き分けになった．
+    Draw { reason: OutcomeReason },
+}
+
+impl GameOutcome {
+    /// 勝者`actor`を`Some(actor)`として返す．引き分けの場合は`None`を返す．
+    pub fn winner(&self) -> Option<Actor> {
+        match self {
+            GameOutcome::Win { winner, .. } => Some(*winner),
+            GameOutcome::Draw { .. } => None,
+        }
+    }
+
+    /// ゲームが終了した理由を返す．
+    pub fn reason(&self) -> OutcomeReason {
+        match self {
+            GameOutcome::Win { reason, .. } | GameOutcome::Draw { reason } => *reason,
+        }
+    }
+}
+
+impl Display for GameOutcome {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GameOutcome::Win { winner, reason } => write!(f, "{:?} wins ({:?})", winner, reason),
+            GameOutcome::Draw { reason } => write!(f, "Draw ({:?})", reason),
+        }
+    }
+}
+
+impl FromStr for OutcomeReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Escape" => Ok(OutcomeReason::Escape),
+            "CapturedAllHoly" => Ok(OutcomeReason::CapturedAllHoly),
+            "LostAllEvil" => Ok(OutcomeReason::LostAllEvil),
+            "MoveLimit" => Ok(OutcomeReason::MoveLimit),
+            "Resignation" => Ok(OutcomeReason::Resignation),
+            _ => Err(format!("Unknown outcome reason: {}", s)),
+        }
+    }
+}
+
+/// `First wins (Escape)`や`Draw (MoveLimit)`のような，`Display`と同じ表記から結果を生成する．
+impl FromStr for GameOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid outcome: {}", s);
+        let open = s.find(" (").ok_or_else(invalid)?;
+        if !s.ends_with(')') {
+            return Err(invalid());
+        }
+        let reason = s[open + 2..s.len() - 1].parse()?;
+        match &s[..open] {
+            "First wins" => Ok(GameOutcome::Win {
+                winner: Actor::First,
+                reason,
+            }),
+            "Second wins" => Ok(GameOutcome::Win {
+                winner: Actor::Second,
+                reason,
+            }),
+            "Draw" => Ok(GameOutcome::Draw { reason }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        let reasons = [
+            OutcomeReason::Escape,
+            OutcomeReason::CapturedAllHoly,
+            OutcomeReason::LostAllEvil,
+            OutcomeReason::MoveLimit,
+            OutcomeReason::Resignation,
+        ];
+        for &reason in reasons.iter() {
+            let outcomes = [
+                GameOutcome::Win {
+                    winner: Actor::First,
+                    reason,
+                },
+                GameOutcome::Win {
+                    winner: Actor::Second,
+                    reason,
+                },
+                GameOutcome::Draw { reason },
+            ];
+            for &outcome in outcomes.iter() {
+                assert_eq!(Ok(outcome), outcome.to_string().parse());
+                assert_eq!(reason, outcome.reason());
+            }
+        }
+        assert_eq!(
+            "First wins (Escape)",
+            GameOutcome::Win {
+                winner: Actor::First,
+                reason: OutcomeReason::Escape,
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "Draw (MoveLimit)",
+            GameOutcome::Draw {
+                reason: OutcomeReason::MoveLimit,
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn from_str_rejects_invalid_outcome() {
+        let invalid_outcomes = [
+            "",
+            "*",
+            "First wins",
+            "First wins (Escape",
+            "First wins (Unknown)",
+            "Third wins (Escape)",
+            "Draw ()",
+            "draw (MoveLimit)",
+        ];
+        for s in invalid_outcomes.iter() {
+            assert!(s.parse::<GameOutcome>().is_err(), "{}", s);
+        }
+    }
+}
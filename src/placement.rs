@@ -0,0 +1,326 @@
+use crate::{Geister, GeisterRules, Setup};
+use data_structure::TableIndex;
+use minimax_strategy::Actor;
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use std::path::Path;
+use std::str::FromStr;
+
+/// 👻の初期配置の決め方．
+/// 人間もコンピュータもこれを実装し，ゲーム開始前に自分の初期配置を決める．
+pub trait InitialPlacement {
+    /// 指定したルールで，指定したプレイヤーの初期配置を決める．
+    /// 乱数が必要な場合は`rng`だけを使い，同じ乱数列からは同じ初期配置を返す．
+    fn select_setup(&self, rules: &GeisterRules, actor: Actor, rng: &mut dyn RngCore) -> Setup;
+}
+
+/// 👻を初期配置の領域にまったくランダムに置く．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomPlacement;
+
+/// 善良な👻をなるべく前の行に置く．同じ行の中ではランダムに置く．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HolyFrontPlacement;
+
+/// 善良な👻をなるべく左右の端の列に置く．同じ列の中ではランダムに置く．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HolyFlankPlacement;
+
+/// 初期配置の領域の各マスに善良な👻を置く重みを指定し，その重みに比例した確率で善良な👻を置く．
+/// 残りのマスには邪悪な👻を置く．
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedRandomPlacement {
+    /// 各マスに善良な👻を置く重み．
+    /// `weights[行][列]`として参照する．行はプレイヤーから見て前から，列は左から数える．
+    weights: Vec<Vec<f64>>,
+}
+
+impl InitialPlacement for RandomPlacement {
+    fn select_setup(&self, rules: &GeisterRules, actor: Actor, rng: &mut dyn RngCore) -> Setup {
+        place_in_order_of(rules, actor, rng, |_| 0)
+    }
+}
+
+impl InitialPlacement for HolyFrontPlacement {
+    fn select_setup(&self, rules: &GeisterRules, actor: Actor, rng: &mut dyn RngCore) -> Setup {
+        place_in_order_of(rules, actor, rng, |relative_position| relative_position.y)
+    }
+}
+
+impl InitialPlacement for HolyFlankPlacement {
+    fn select_setup(&self, rules: &GeisterRules, actor: Actor, rng: &mut dyn RngCore) -> Setup {
+        let column_count = rules.field_size().x - 2;
+        place_in_order_of(rules, actor, rng, |relative_position| {
+            relative_position
+                .x
+                .min(column_count - 1 - relative_position.x)
+        })
+    }
+}
+
+impl WeightedRandomPlacement {
+    /// 指定したファイルから重みを読み込む．
+    /// ファイルの書式は`from_str()`と同じ．
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?
+            .parse()
+    }
+
+    /// 重みの行数・列数が，指定したルールの初期配置の領域と合っているか確かめる．
+    pub fn check_size_for(&self, rules: &GeisterRules) -> Result<(), String> {
+        let row_count = rules.setup_row_count();
+        let column_count = rules.field_size().x - 2;
+        if self.weights.len() == row_count
+            && self.weights.iter().all(|row| row.len() == column_count)
+        {
+            Ok(())
+        } else {
+            Err(format!(
+                "Weights must have {} rows of {} columns",
+                row_count, column_count
+            ))
+        }
+    }
+}
+
+impl InitialPlacement for WeightedRandomPlacement {
+    fn select_setup(&self, rules: &GeisterRules, actor: Actor, rng: &mut dyn RngCore) -> Setup {
+        let mut available_positions = rules.setup_positions_of(actor);
+        let mut setup = Setup::new(actor);
+
+        // 重みに比例した確率で，善良な👻を置くマスを一つずつ選ぶ
+        for _ in 0..rules.initial_geister_count(Geister::Holy) {
+            let weight_of = |position: &TableIndex| {
+                let relative_position = relative_position_of(rules, actor, *position);
+                self.weights
+                    .get(relative_position.y)
+                    .and_then(|row| row.get(relative_position.x))
+                    .cloned()
+                    .unwrap_or(0.0)
+            };
+            // 残ったマスの重みがすべて0の場合は，一様な確率で選ぶ
+            let position = match available_positions.choose_weighted(rng, weight_of) {
+                Ok(&position) => position,
+                Err(_) => *available_positions
+                    .choose(rng)
+                    .expect("Setup zone must have enough positions"),
+            };
+            available_positions.retain(|&p| p != position);
+            setup
+                .place(position, Geister::Holy)
+                .expect("Setup positions must be distinct");
+        }
+
+        // 残りのマスから，邪悪な👻を置くマスをランダムに選ぶ
+        available_positions.shuffle(rng);
+        for position in available_positions
+            .into_iter()
+            .take(rules.initial_geister_count(Geister::Evil))
+        {
+            setup
+                .place(position, Geister::Evil)
+                .expect("Setup positions must be distinct");
+        }
+
+        setup
+    }
+}
+
+/// 初期配置の領域の行ごとに，空白で区切った重みを並べた表記から生成する．
+/// 行はプレイヤーから見て前の行から順に，各行の重みは左のマスから順に並べる．
+/// 空行と`#`で始まる行は無視する．
+impl FromStr for WeightedRandomPlacement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|weight| match weight.parse::<f64>() {
+                        Ok(weight) if weight >= 0.0 && weight.is_finite() => Ok(weight),
+                        _ => Err(format!("Invalid weight: {}", weight)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let column_count = weights.first().map(Vec::len).unwrap_or(0);
+        if column_count == 0 {
+            return Err("No weights are given".to_string());
+        }
+        if weights.iter().any(|row| row.len() != column_count) {
+            return Err("All rows must have the same number of weights".to_string());
+        }
+
+        Ok(Self { weights })
+    }
+}
+
+/// 初期配置の領域の各マスに優先度をつけ，優先度の高いマスから善良な👻，邪悪な👻の順に置く．
+/// 同じ優先度のマスの間ではランダムに選ぶ．
+/// # Params
+/// 1. `priority_of` プレイヤーから見た領域内の位置(`relative_position_of()`を参照)を受け取り，優先度を返す関数．値が小さいほど優先度が高い．
+fn place_in_order_of<F>(
+    rules: &GeisterRules,
+    actor: Actor,
+    rng: &mut dyn RngCore,
+    priority_of: F,
+) -> Setup
+where
+    F: Fn(TableIndex) -> usize,
+{
+    // 配置可能な位置をランダムな順序にしてから優先度で並べ替えることで，同じ優先度のマスの順序をランダムにする
+    let mut available_positions = rules.setup_positions_of(actor);
+    available_positions.shuffle(rng);
+    available_positions
+        .sort_by_key(|&position| priority_of(relative_position_of(rules, actor, position)));
+
+    // 配置可能位置の先頭から善良な👻，邪悪な👻の順に初期個数ずつ要素を取り出して，各👻の初期位置とする．
+    let mut setup = Setup::new(actor);
+    let mut available_positions = available_positions.into_iter();
+    for &geister in [Geister::Holy, Geister::Evil].iter() {
+        for position in available_positions
+            .by_ref()
+            .take(rules.initial_geister_count(geister))
+        {
+            setup
+                .place(position, geister)
+                .expect("Setup positions must be distinct");
+        }
+    }
+
+    setup
+}
+
+/// 初期配置の領域内の位置を，プレイヤーから見た位置に変換する．
+/// 変換後の位置は，最も前の行の最も左のマスを`(0, 0)`とし，右と後ろへ向かって増える．
+fn relative_position_of(rules: &GeisterRules, actor: Actor, position: TableIndex) -> TableIndex {
+    let size = rules.field_size();
+    match actor {
+        // 先手の初期配置の領域は下側にあり，上が前になる
+        Actor::First => TableIndex::new(
+            position.x - 1,
+            position.y - (size.y - rules.setup_row_count()),
+        ),
+        // 後手の初期配置の領域は上側にあり，盤面を180度回転して見る
+        Actor::Second => TableIndex::new(
+            size.x - 2 - position.x,
+            rules.setup_row_count() - 1 - position.y,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_structure::TableSize;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// 指定したルールの初期配置の領域に合う，前の行ほど重い重みを返す．
+    fn weights_for(rules: &GeisterRules) -> WeightedRandomPlacement {
+        let column_count = rules.field_size().x - 2;
+        (0..rules.setup_row_count())
+            .map(|row| vec![(rules.setup_row_count() - row).to_string(); column_count].join(" "))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn every_placement_yields_valid_setup() {
+        let rules_list = [
+            GeisterRules::standard(),
+            GeisterRules::with_field_size(TableSize::new(8, 8)).unwrap(),
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        for rules in rules_list.iter() {
+            let weighted = weights_for(rules);
+            assert_eq!(Ok(()), weighted.check_size_for(rules));
+            let placements: Vec<&dyn InitialPlacement> = vec![
+                &RandomPlacement,
+                &HolyFrontPlacement,
+                &HolyFlankPlacement,
+                &weighted,
+            ];
+            for placement in placements.into_iter() {
+                for &actor in minimax_strategy::actors().iter() {
+                    for _ in 0..10 {
+                        let setup = placement.select_setup(rules, actor, &mut rng);
+                        assert_eq!(actor, setup.actor());
+                        assert_eq!(Ok(()), setup.validate(rules));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn holy_front_placement_puts_holy_geisters_in_front() {
+        let rules = GeisterRules::standard();
+        let mut rng = StdRng::seed_from_u64(0);
+        let setup = HolyFrontPlacement.select_setup(&rules, Actor::First, &mut rng);
+        for x in 1..5 {
+            assert_eq!(Some(Geister::Holy), setup.geister_at(TableIndex::new(x, 4)));
+        }
+    }
+
+    #[test]
+    fn weights_from_str_accepts_comments_and_blank_lines() {
+        let weighted = "# front\n1 2 3 4\n\n0 0.5 0 1e3\n"
+            .parse::<WeightedRandomPlacement>()
+            .unwrap();
+        assert_eq!(
+            vec![vec![1.0, 2.0, 3.0, 4.0], vec![0.0, 0.5, 0.0, 1000.0]],
+            weighted.weights
+        );
+    }
+
+    #[test]
+    fn weights_from_str_rejects_invalid_weights() {
+        let invalid_weights = [
+            "",
+            "# only comment",
+            "1 1 1 1\n1 1 1",
+            "1 -1 1 1\n1 1 1 1",
+            "1 1 x 1\n1 1 1 1",
+            "1 1 NaN 1\n1 1 1 1",
+            "1 1 inf 1\n1 1 1 1",
+        ];
+        for s in invalid_weights.iter() {
+            assert!(s.parse::<WeightedRandomPlacement>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn check_size_for_rejects_wrong_size() {
+        let rules = GeisterRules::standard();
+        let wrong_weights = ["1 1 1 1", "1 1 1\n1 1 1", "1 1 1 1\n1 1 1 1\n1 1 1 1"];
+        for s in wrong_weights.iter() {
+            let weighted = s.parse::<WeightedRandomPlacement>().unwrap();
+            assert!(weighted.check_size_for(&rules).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn load_reads_weights_from_file() {
+        let rules = GeisterRules::standard();
+        let path =
+            std::env::temp_dir().join(format!("geister_placement_test_{}.txt", std::process::id()));
+        std::fs::write(&path, "1 2 2 1\n1 1 1 1\n").unwrap();
+        let loaded = WeightedRandomPlacement::load(&path);
+        std::fs::write(&path, "1 2 2 1\n1 -1 1 1\n").unwrap();
+        let negative = WeightedRandomPlacement::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Ok(()), loaded.unwrap().check_size_for(&rules));
+        assert!(negative.is_err());
+        assert!(WeightedRandomPlacement::load(&path).is_err());
+    }
+}
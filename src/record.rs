@@ -0,0 +1,562 @@
+use crate::{
+    ActionNotation, GameOutcome, GeisterAction, GeisterRule, GeisterState, IllegalAction,
+    OutcomeReason, OwnedGeister, Undo,
+};
+use data_structure::TableIndex;
+use minimax_strategy::{Actor, Rule};
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 棋譜ファイルの書式とその版．書式を変更したら版を上げる．
+pub const RECORD_FORMAT: &str = "geister-record 1";
+
+/// 👻が取られたことの記録．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaptureEvent {
+    /// 👻を取った行動の，この記録の`actions()`における位置．`actions()[ply]`が👻を取った行動である．
+    /// 初期状態の手数が0でない場合は，ゲーム開始からの手数とは異なる．
+    pub ply: usize,
+    /// 👻が取られた位置．
+    pub position: TableIndex,
+    /// 取られた👻．
+    pub captured_geister: OwnedGeister,
+}
+
+impl CaptureEvent {
+    /// 👻を取ったプレイヤーを返す．
+    pub fn capturer(&self) -> Actor {
+        self.captured_geister.owner.opponent()
+    }
+}
+
+/// 棋譜に付ける，盤面からは分からない対局の情報．
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordTags {
+    /// 先手のプレイヤーの説明．
+    pub first: Option<String>,
+    /// 後手のプレイヤーの説明．
+    pub second: Option<String>,
+    /// 初期配置やコンピュータの行動を決めた乱数の種．
+    pub seed: Option<u64>,
+    /// 対局した日付．`2020.01.31`のように表す．
+    pub date: Option<String>,
+}
+
+/// 1回のゲームの棋譜．
+/// 初期状態と，そこから順に適用した行動，取られた👻，ゲームの結果を保持する．
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    /// ゲーム開始時の状態．両プレイヤーの初期配置を含む．
+    initial_state: GeisterState,
+    /// 適用した行動．適用した順に並べる．
+    actions: Vec<GeisterAction>,
+    /// 各行動を取り消すために必要な情報．`actions`と同じ順に並べる．
+    undos: Vec<Undo>,
+    /// 👻が取られたことの記録．取られた順に並べる．
+    captures: Vec<CaptureEvent>,
+    /// すべての行動を適用した後の状態．
+    current_state: GeisterState,
+    /// ゲームの結果．ゲームが終了していない場合は`None`．
+    outcome: Option<GameOutcome>,
+    /// 対局の情報．
+    tags: RecordTags,
+}
+
+impl GameRecord {
+    /// 指定した初期状態から始まる，行動がひとつも記録されていない棋譜を生成する．
+    pub fn new(initial_state: GeisterState) -> Self {
+        Self {
+            current_state: initial_state.clone(),
+            initial_state,
+            actions: vec![],
+            undos: vec![],
+            captures: vec![],
+            outcome: None,
+            tags: RecordTags::default(),
+        }
+    }
+
+    /// ゲーム開始時の状態を返す．
+    pub fn initial_state(&self) -> &GeisterState {
+        &self.initial_state
+    }
+
+    /// 記録されたすべての行動を，適用した順に返す．
+    pub fn actions(&self) -> &[GeisterAction] {
+        &self.actions
+    }
+
+    /// 👻が取られたことの記録を，取られた順に返す．
+    pub fn captures(&self) -> &[CaptureEvent] {
+        &self.captures
+    }
+
+    /// 記録されたすべての行動を適用した後の状態を返す．
+    pub fn current_state(&self) -> &GeisterState {
+        &self.current_state
+    }
+
+    /// ゲームの結果`outcome`を`Some(outcome)`として返す．
+    /// 結果が記録されていない場合は`None`を返す．
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        self.outcome
+    }
+
+    /// ゲームの結果を記録する．
+    pub fn set_outcome(&mut self, outcome: GameOutcome) {
+        self.outcome = Some(outcome);
+    }
+
+    /// 対局の情報を返す．
+    pub fn tags(&self) -> &RecordTags {
+        &self.tags
+    }
+
+    /// 対局の情報を記録する．
+    pub fn set_tags(&mut self, tags: RecordTags) {
+        self.tags = tags;
+    }
+
+    /// 現在の状態に指定した行動を適用し，その行動を記録する．
+    /// ルール上とれない行動の場合は何も記録せず，その理由を返す．
+    pub fn push(&mut self, action: GeisterAction) -> Result<(), IllegalAction> {
+        GeisterRule::validate(&self.current_state, &action)?;
+
+        let ply = self.actions.len();
+        let undo = GeisterRule::apply(&mut self.current_state, &action);
+        if let Some(captured_geister) = undo.killed_geister() {
+            self.captures.push(CaptureEvent {
+                ply,
+                position: action
+                    .position_after_movement()
+                    .expect("Captures occur only by movement"),
+                captured_geister,
+            });
+        }
+        self.actions.push(action);
+        self.undos.push(undo);
+        Ok(())
+    }
+
+    /// 最後に記録した行動を取り消して返す．
+    /// 行動がひとつも記録されていない場合は`None`を返す．
+    pub fn pop(&mut self) -> Option<GeisterAction> {
+        let action = self.actions.pop()?;
+        let undo = self.undos.pop().expect("Each action must have its undo");
+        GeisterRule::undo(&mut self.current_state, undo);
+        let ply = self.actions.len();
+        self.captures.retain(|capture| capture.ply < ply);
+        self.outcome = None;
+        Some(action)
+    }
+
+    /// 初期状態に記録された行動を先頭から指定した数だけ適用した状態`state`を`Some(state)`として返す．
+    /// 指定した数が記録された行動の数を超える場合は`None`を返す．
+    pub fn state_at(&self, ply: usize) -> Option<GeisterState> {
+        if ply > self.actions.len() {
+            return None;
+        }
+
+        // 現在の状態から，指定した数より後の行動を新しいものから順に取り消す
+        let mut state = self.current_state.clone();
+        for &undo in self.undos[ply..].iter().rev() {
+            GeisterRule::undo(&mut state, undo);
+        }
+        Some(state)
+    }
+}
+
+impl GameRecord {
+    /// 棋譜を指定したファイルに書き出す．書式は`Display`の実装を参照．
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string())
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+    }
+
+    /// 指定したファイルから棋譜を読み込む．書式は`Display`の実装を参照．
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?
+            .parse()
+    }
+}
+
+/// PGNに倣った次のような書式で棋譜を書き出す．
+///
+/// ```text
+/// [Format "geister-record 1"]
+/// [First "human"]
+/// [Second "ai"]
+/// [Seed "42"]
+/// [Date "2020.01.31"]
+/// [Rules "6x6:4:4:300"]
+/// [Setup "1hhee1/1eheh1/6/6/1EEHE1/1HEHH1 f 0,0,0,0 - 0 6x6:4:4:300"]
+/// [Result "First wins (Escape)"]
+///
+/// 1. d2-d3 e5-e4 2. d3-d4 d5-d4 ...
+/// ```
+///
+/// 1. 先頭には`[名前 "値"]`の形のタグを並べる．値の中の`"`と`\`は`\`でエスケープする．
+///    `Format`は書式の版を表し，必ず最初に置く．
+///    `Setup`は初期状態を局面の表記で，`Result`はゲームの結果を`GameOutcome`の表記で表し，
+///    ゲームが終了していない場合は`*`とする．値の分からないタグは省略する．
+/// 1. 空行の後に，行動を先手から見た行動の表記(絶対表記)で順に並べる．
+///    先手の行動の前には`1.`のような手順の番号を置く．
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_tag(f, "Format", RECORD_FORMAT)?;
+        if let Some(first) = &self.tags.first {
+            write_tag(f, "First", first)?;
+        }
+        if let Some(second) = &self.tags.second {
+            write_tag(f, "Second", second)?;
+        }
+        if let Some(seed) = self.tags.seed {
+            write_tag(f, "Seed", &seed.to_string())?;
+        }
+        if let Some(date) = &self.tags.date {
+            write_tag(f, "Date", date)?;
+        }
+        write_tag(f, "Rules", &self.initial_state.rules().to_string())?;
+        write_tag(f, "Setup", &self.initial_state.to_string())?;
+        match self.outcome {
+            Some(outcome) => write_tag(f, "Result", &outcome.to_string())?,
+            None => write_tag(f, "Result", "*")?,
+        }
+        writeln!(f)?;
+
+        let field_size = self.initial_state.rules().field_size();
+        let mut tokens = vec![];
+        for (i, action) in self.actions.iter().enumerate() {
+            let ply = self.initial_state.ply() + i;
+            if action.actor == Actor::First {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            tokens.push(ActionNotation::of(action, field_size, Actor::First).to_string());
+        }
+        // 1行が長くなりすぎないよう，先手の手順ごとに折り返す
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + token.len() > 80 && token.ends_with('.') {
+                writeln!(f)?;
+                line_length = 0;
+            }
+            if line_length > 0 {
+                write!(f, " ")?;
+                line_length += 1;
+            }
+            write!(f, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+/// `Display`で書き出した書式から棋譜を読み込む．
+/// 行動はすべて検証し，ルール上とれない行動があればエラーを返す．
+impl FromStr for GameRecord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim);
+
+        // タグ
+        let mut tags = RecordTags::default();
+        let mut format = None;
+        let mut rules = None;
+        let mut setup = None;
+        let mut result = None;
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = parse_tag(line)?;
+            match name {
+                "Format" => format = Some(value),
+                "First" => tags.first = Some(value),
+                "Second" => tags.second = Some(value),
+                "Seed" => {
+                    tags.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid seed: {}", value))?,
+                    )
+                }
+                "Date" => tags.date = Some(value),
+                "Rules" => rules = Some(value),
+                "Setup" => setup = Some(value),
+                "Result" => result = Some(value),
+                // 知らないタグは読み飛ばす
+                _ => {}
+            }
+        }
+        match format {
+            Some(ref format) if format == RECORD_FORMAT => {}
+            Some(format) => return Err(format!("Unsupported format: {}", format)),
+            None => return Err("Format tag is missing".to_string()),
+        }
+        let initial_state = setup
+            .ok_or_else(|| "Setup tag is missing".to_string())?
+            .parse::<GeisterState>()?;
+        if let Some(rules) = rules {
+            if rules != initial_state.rules().to_string() {
+                return Err(format!("Rules tag does not match the setup: {}", rules));
+            }
+        }
+
+        // 行動
+        let mut record = GameRecord::new(initial_state);
+        record.set_tags(tags);
+        let field_size = record.initial_state.rules().field_size();
+        for token in lines.flat_map(str::split_whitespace) {
+            // 手順の番号は読み飛ばす
+            if token.ends_with('.') && token[..token.len() - 1].parse::<usize>().is_ok() {
+                continue;
+            }
+            let actor = record.current_state.current_actor();
+            let ply = record.current_state.ply();
+            let action = token
+                .parse::<ActionNotation>()?
+                .to_action(field_size, Actor::First, actor)
+                .and_then(|action| record.push(action));
+            if let Err(e) = action {
+                return Err(format!("Ply {}: {}: {}", ply + 1, token, e));
+            }
+        }
+
+        // 結果
+        match result.as_deref() {
+            None | Some("*") => {
+                if GeisterRule::is_game_over(&record.current_state) {
+                    return Err("The game is over but its result is missing".to_string());
+                }
+            }
+            Some(result) => {
+                let outcome = result.parse::<GameOutcome>()?;
+                // 盤面から決まる結果と矛盾していないか確かめる
+                // 盤面上は決着していない対局は，投了でしか終わらない
+                match GeisterRule::outcome_of(&record.current_state) {
+                    Some(expected) if outcome != expected => {
+                        return Err(format!("Result must be {}", expected));
+                    }
+                    None if outcome.winner().is_none()
+                        || outcome.reason() != OutcomeReason::Resignation =>
+                    {
+                        return Err(format!("Result {} does not match the board", outcome));
+                    }
+                    _ => {}
+                }
+                record.set_outcome(outcome);
+            }
+        }
+
+        Ok(record)
+    }
+}
+
+/// 今日の日付を`2020.01.31`のような表記で返す(UTC)．
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+/// 1970年1月1日からの日数(0以上)を，グレゴリオ暦の年・月・日に変換する．
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// `[名前 "値"]`の形のタグを1行書き込む．
+fn write_tag(f: &mut Formatter, name: &str, value: &str) -> fmt::Result {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    writeln!(f, "[{} \"{}\"]", name, value)
+}
+
+/// `[名前 "値"]`の形のタグを読み取り，名前とエスケープを戻した値を返す．
+fn parse_tag(line: &str) -> Result<(&str, String), String> {
+    let invalid = || format!("Invalid tag: {}", line);
+    if !line.starts_with('[') || !line.ends_with(']') {
+        return Err(invalid());
+    }
+    let line_body = &line[1..line.len() - 1];
+    let space = line_body.find(' ').ok_or_else(invalid)?;
+    let (name, quoted) = (&line_body[..space], line_body[space + 1..].trim());
+    if quoted.len() < 2 || !quoted.starts_with('"') || !quoted.ends_with('"') {
+        return Err(invalid());
+    }
+
+    let mut value = String::new();
+    let mut chars = quoted[1..quoted.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next().ok_or_else(invalid)?),
+            '"' => return Err(invalid()),
+            _ => value.push(c),
+        }
+    }
+    Ok((name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeisterRules, InitialPlacement, RandomPlacement};
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    /// 先手から見た絶対表記の行動を，手番のプレイヤーの行動として記録する．
+    fn push_notation(record: &mut GameRecord, s: &str) {
+        let field_size = record.initial_state().rules().field_size();
+        let actor = record.current_state().current_actor();
+        let action = s
+            .parse::<ActionNotation>()
+            .unwrap()
+            .to_action(field_size, Actor::First, actor)
+            .unwrap();
+        record.push(action).unwrap();
+    }
+
+    /// 2つの棋譜が同じ対局を表していることを確かめる．
+    fn assert_same_record(expected: &GameRecord, actual: &GameRecord) {
+        assert_eq!(expected.tags(), actual.tags());
+        assert_eq!(expected.initial_state(), actual.initial_state());
+        assert_eq!(expected.actions(), actual.actions());
+        assert_eq!(expected.captures(), actual.captures());
+        assert_eq!(expected.current_state(), actual.current_state());
+        assert_eq!(expected.outcome(), actual.outcome());
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    /// 👻を取る行動と，エスケープの必要なタグを含む棋譜を返す．
+    fn sample_record() -> GameRecord {
+        let initial_state = "1hhhh1/1eeee1/6/6/1EEEE1/1HHHH1 f 0,0,0,0 - 0 6x6:4:4:300"
+            .parse::<GeisterState>()
+            .unwrap();
+        let mut record = GameRecord::new(initial_state);
+        record.set_tags(RecordTags {
+            first: Some("human \"Alice\"".to_string()),
+            second: Some("ai (depth 3) \\ test".to_string()),
+            seed: Some(42),
+            date: Some("2020.01.31".to_string()),
+        });
+        for s in ["b2-b3", "b5-b4", "b3-b4", "c5-c4"].iter() {
+            push_notation(&mut record, s);
+        }
+        record.set_outcome(GameOutcome::Win {
+            winner: Actor::First,
+            reason: OutcomeReason::Resignation,
+        });
+        record
+    }
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        let record = sample_record();
+        assert_eq!(1, record.captures().len());
+        let parsed = record.to_string().parse::<GameRecord>().unwrap();
+        assert_same_record(&record, &parsed);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let record = sample_record();
+        let path =
+            std::env::temp_dir().join(format!("geister_record_test_{}.txt", std::process::id()));
+        record.save(&path).unwrap();
+        let loaded = GameRecord::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_same_record(&record, &loaded.unwrap());
+    }
+
+    #[test]
+    fn from_str_rejects_invalid_record() {
+        let valid = sample_record().to_string();
+        let invalid_records = [
+            // 書式の版がない
+            valid.replace("[Format \"geister-record 1\"]\n", ""),
+            // ルール上とれない行動がある
+            valid.replace("c5-c4", "c5-c3"),
+            // 結果が盤面と矛盾する
+            valid.replace("First wins (Resignation)", "First wins (Escape)"),
+            // ルールが初期状態と一致しない
+            valid.replace("[Rules \"6x6:4:4:300\"]", "[Rules \"6x6:4:4\"]"),
+        ];
+        for s in invalid_records.iter() {
+            assert_ne!(&valid, s);
+            assert!(s.parse::<GameRecord>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn push_rejects_out_of_turn_action() {
+        let initial_state = "1hhhh1/1eeee1/6/6/1EEEE1/1HHHH1 f 0,0,0,0 - 0 6x6:4:4"
+            .parse::<GeisterState>()
+            .unwrap();
+        let mut record = GameRecord::new(initial_state.clone());
+        // 後手の`b6-a6`は，先手の手番にはとれない
+        let action = "b6-a6"
+            .parse::<ActionNotation>()
+            .unwrap()
+            .to_action(
+                initial_state.rules().field_size(),
+                Actor::First,
+                Actor::Second,
+            )
+            .unwrap();
+        assert_eq!(Err(IllegalAction::NotYourTurn), record.push(action));
+        assert!(record.actions().is_empty());
+        assert!(record.captures().is_empty());
+        assert_eq!(&initial_state, record.current_state());
+        assert_eq!(0, record.current_state().ply());
+    }
+
+    #[test]
+    fn pop_and_state_at_match_states_after_push() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let rules = GeisterRules::default();
+            let first_setup = RandomPlacement.select_setup(&rules, Actor::First, &mut rng);
+            let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, &mut rng);
+            let initial_state =
+                GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+            let mut record = GameRecord::new(initial_state.clone());
+            let mut states = vec![initial_state];
+            while !GeisterRule::is_game_over(record.current_state()) && states.len() <= 200 {
+                let state = record.current_state();
+                let actions = GeisterRule::iterate_available_actions(state, state.current_actor());
+                let action = *actions.choose(&mut rng).unwrap();
+                record.push(action).unwrap();
+                states.push(record.current_state().clone());
+            }
+
+            for (ply, state) in states.iter().enumerate() {
+                assert_eq!(Some(state), record.state_at(ply).as_ref());
+            }
+            assert_eq!(None, record.state_at(states.len()));
+            while record.pop().is_some() {
+                let ply = record.actions().len();
+                assert_eq!(&states[ply], record.current_state());
+                assert_eq!(states[ply].ply(), record.current_state().ply());
+                assert!(record.captures().iter().all(|capture| capture.ply < ply));
+            }
+        }
+    }
+}
@@ -1,152 +1,537 @@
 use crate::{
-    Geister, GeisterAction, GeisterMovement, GeisterState, OwnedGeister, AVAILABLE_ACTIONS,
-    FIELD_SIZE, INITIAL_GEISTER_COUNT,
+    GameOutcome, Geister, GeisterAction, GeisterMovement, GeisterRules, GeisterState,
+    OutcomeReason, OwnedGeister, AVAILABLE_ACTIONS,
 };
 use data_structure::{Pair, TableIndex};
 use minimax_strategy::{actors, Action, Actor, Rule};
+use std::fmt::{self, Display, Formatter};
 
 enum GeisterStateAfterAction {
     OnField(TableIndex),
     Clear,
 }
 
+/// 行動がルール上とれない理由を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IllegalAction {
+    /// 動かす👻の位置，または移動先がフィールドの外にある．
+    OffBoard,
+    /// 移動先に自分の👻がいる．
+    OwnGeisterOnTarget,
+    /// 動かそうとした位置に👻がいない．
+    NoGeisterAtSource,
+    /// 動かそうとした👻が，行動するプレイヤーの👻ではない．
+    OpponentGeister,
+    /// 邪悪な👻をフィールドから上がらせようとした．
+    ClearingEvilGeister,
+    /// 脱出マス以外から👻を上がらせようとした．
+    ClearingFromNonExit,
+    /// ゲームが既に終了している．
+    GameOver,
+    /// 行動するプレイヤーの手番ではない．
+    NotYourTurn,
+}
+
+impl Display for IllegalAction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let message = match self {
+            IllegalAction::OffBoard => "The geister cannot move off the field",
+            IllegalAction::OwnGeisterOnTarget => "Your own geister is on the target square",
+            IllegalAction::NoGeisterAtSource => "There is no geister to move",
+            IllegalAction::OpponentGeister => "The geister belongs to the opponent",
+            IllegalAction::ClearingEvilGeister => "An evil geister cannot clear",
+            IllegalAction::ClearingFromNonExit => "Geisters can clear only from an exit square",
+            IllegalAction::GameOver => "The game is already over",
+            IllegalAction::NotYourTurn => "It is not your turn",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for IllegalAction {}
+
 pub struct GeisterRule {}
 
 impl GeisterRule {
-    /// 指定した状態におけるゲームの勝者`actor`を`Some(actor)`として返す．
-    /// 勝者が決定してない場合は`None`を返す．
-    pub fn winner_of(state: &GeisterState) -> Option<Actor> {
-        if let Some(winner) = state.actor_of_cleared_geister {
-            return Some(winner);
+    /// 指定した手数が進んだときの手番のプレイヤーを返す．先手から交互に行動する．
+    pub fn actor_on_ply(ply: usize) -> Actor {
+        if ply.is_multiple_of(2) {
+            Actor::First
+        } else {
+            Actor::Second
+        }
+    }
+
+    /// 指定した状態におけるゲームの結果`outcome`を`Some(outcome)`として返す．
+    /// ゲームが終了していない場合は`None`を返す．
+    /// 投了は状態からは判断できないため，ここでは扱わない．
+    pub fn outcome_of(state: &GeisterState) -> Option<GameOutcome> {
+        Self::outcome_from_parts(
+            state.rules(),
+            |owned_geister| state.killed_geister_count(owned_geister),
+            state.actor_of_cleared_geister(),
+            state.ply(),
+        )
+    }
+
+    /// 状態を構成する要素から，`outcome_of()`と同じようにゲームの結果を判定する．
+    /// `GeisterState`以外の表現の状態でも同じ判定を使えるよう，要素ごとに受け取る．
+    /// # Params
+    /// 1. `rules` ゲームのルール．
+    /// 1. `killed_geister_count` 指定した👻が何体フィールドから取り除かれたか返す関数．
+    /// 1. `actor_of_cleared_geister` フィールドから上がった👻の所有者．
+    /// 1. `ply` ゲーム開始から進んだ手数．
+    pub fn outcome_from_parts<F>(
+        rules: &GeisterRules,
+        killed_geister_count: F,
+        actor_of_cleared_geister: Option<Actor>,
+        ply: usize,
+    ) -> Option<GameOutcome>
+    where
+        F: Fn(OwnedGeister) -> usize,
+    {
+        if let Some(winner) = actor_of_cleared_geister {
+            return Some(GameOutcome::Win {
+                winner,
+                reason: OutcomeReason::Escape,
+            });
         }
         for &actor in actors().iter() {
-            if state.killed_geister_count(OwnedGeister::new(Geister::Evil, actor))
-                == INITIAL_GEISTER_COUNT
+            if killed_geister_count(OwnedGeister::new(Geister::Evil, actor))
+                == rules.initial_geister_count(Geister::Evil)
             {
-                return Some(actor);
-            } else if state.killed_geister_count(OwnedGeister::new(Geister::Holy, actor))
-                == INITIAL_GEISTER_COUNT
+                return Some(GameOutcome::Win {
+                    winner: actor,
+                    reason: OutcomeReason::LostAllEvil,
+                });
+            } else if killed_geister_count(OwnedGeister::new(Geister::Holy, actor))
+                == rules.initial_geister_count(Geister::Holy)
             {
-                return Some(actor.opponent());
+                return Some(GameOutcome::Win {
+                    winner: actor.opponent(),
+                    reason: OutcomeReason::CapturedAllHoly,
+                });
             }
         }
-        None
+        match rules.max_ply() {
+            Some(max_ply) if ply >= max_ply => Some(GameOutcome::Draw {
+                reason: OutcomeReason::MoveLimit,
+            }),
+            _ => None,
+        }
     }
-}
-
-impl Rule for GeisterRule {
-    type S = GeisterState;
-
-    type A = GeisterAction;
 
-    type ActionIterator = Vec<GeisterAction>;
-
-    fn is_game_over(state: &GeisterState) -> bool {
-        Self::winner_of(state).is_some()
+    /// 指定した状態で指定した行動がとれるか確かめる．
+    /// とれない場合はその理由を返す．
+    pub fn validate(state: &GeisterState, action: &GeisterAction) -> Result<(), IllegalAction> {
+        if Self::is_game_over(state) {
+            return Err(IllegalAction::GameOver);
+        }
+        if action.actor != state.current_actor() {
+            return Err(IllegalAction::NotYourTurn);
+        }
+        get_geister_state_after_action(state, action).map(|_| ())
     }
 
-    fn iterate_available_actions(state: &GeisterState, actor: Actor) -> Self::ActionIterator {
+    /// 指定した状態で指定したプレイヤーがとれる行動を，ヒープを使わずに順に列挙する．
+    /// 列挙の順序は`iterate_available_actions()`と同じである．
+    pub fn legal_actions<'a>(
+        state: &'a GeisterState,
+        actor: Actor,
+    ) -> impl Iterator<Item = GeisterAction> + 'a {
         state
             // フィールド上の，自分の👻がいる位置を求める
-            .lattices
+            .lattices()
             .iter_row()
             .enumerate()
-            .flat_map(|(y, iter_row)| {
+            .flat_map(move |(y, iter_row)| {
                 iter_row
                     .iter()
                     .enumerate()
-                    .filter(|(_x, owned_geister)| owned_geister.map(|g| g.owner) == Some(actor))
+                    .filter(move |(_x, owned_geister)| {
+                        owned_geister.map(|g| g.owner) == Some(actor)
+                    })
                     .map(move |(x, _owned_geister)| Pair::new(x, y))
             })
             // 👻の全動作のうち，実行可能なものだけを求める
-            .flat_map(|owned_geister_position| {
+            .flat_map(move |owned_geister_position| {
                 AVAILABLE_ACTIONS
                     .iter()
                     .map(move |&movement_candidate| {
                         GeisterAction::new(owned_geister_position, movement_candidate, actor)
                     })
-                    .filter(|action_candidate| {
-                        get_geister_state_after_action(state, &action_candidate).is_some()
+                    .filter(move |action_candidate| {
+                        get_geister_state_after_action(state, action_candidate).is_ok()
                     })
             })
-            // ここでヒープに実行可能な行動を保存しているが，
-            // 本当はパフォーマンスの関係上iteratorのまま返したい．
-            .collect::<Vec<_>>()
+    }
+
+    /// 指定した状態で指定したプレイヤーがとれる行動がひとつでもあるか返す．
+    /// 最初の行動が見つかった時点で列挙を打ち切るため，行動を数えるより速い．
+    pub fn has_any_legal_action(state: &GeisterState, actor: Actor) -> bool {
+        Self::legal_actions(state, actor).next().is_some()
+    }
+
+    /// 指定した状態で指定したプレイヤーがとれる行動の数を返す．
+    pub fn count_legal_actions(state: &GeisterState, actor: Actor) -> usize {
+        Self::legal_actions(state, actor).count()
+    }
+}
+
+impl Rule for GeisterRule {
+    type S = GeisterState;
+
+    type A = GeisterAction;
+
+    type ActionIterator = Vec<GeisterAction>;
+
+    fn is_game_over(state: &GeisterState) -> bool {
+        Self::outcome_of(state).is_some()
+    }
+
+    fn iterate_available_actions(state: &GeisterState, actor: Actor) -> Self::ActionIterator {
+        Self::legal_actions(state, actor).collect()
     }
 
     fn translate_state(state: &GeisterState, action: &GeisterAction) -> GeisterState {
         let mut new_state = state.clone();
+        Self::apply(&mut new_state, action);
+        new_state
+    }
+}
+
+/// `GeisterRule::apply()`で適用した行動を取り消すために必要な情報．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Undo {
+    /// 適用した行動．
+    action: GeisterAction,
+    /// 行動した👻．
+    moved_geister: OwnedGeister,
+    /// 行動によってフィールドから取り除かれた相手の👻．
+    killed_geister: Option<OwnedGeister>,
+    /// 行動を適用する前に，フィールドから上がっていた👻の所有者．
+    actor_of_cleared_geister: Option<Actor>,
+}
+
+impl Undo {
+    /// 行動によってフィールドから取り除かれた相手の👻`geister`を`Some(geister)`として返す．
+    /// 相手の👻を取らなかった場合は`None`を返す．
+    pub fn killed_geister(&self) -> Option<OwnedGeister> {
+        self.killed_geister
+    }
+}
 
-        match get_geister_state_after_action(state, action)
-            .expect("translate_state() accepts only available action.")
-        {
+impl GeisterRule {
+    /// 指定した状態に，指定した行動をその場で適用する．
+    /// 返り値を`GeisterRule::undo()`に渡すと，適用前の状態に戻せる．
+    pub fn apply(state: &mut GeisterState, action: &GeisterAction) -> Undo {
+        let state_after_action = get_geister_state_after_action(state, action)
+            .expect("apply() accepts only available action.");
+        let moved_geister = state
+            .geister_at(action.target_geister_position)
+            .expect("Geister must exist at the target position");
+        let mut undo = Undo {
+            action: *action,
+            moved_geister,
+            killed_geister: None,
+            actor_of_cleared_geister: state.actor_of_cleared_geister(),
+        };
+
+        match state_after_action {
             GeisterStateAfterAction::OnField(position_after_movement) => {
                 // 自分の👻を他の自分の👻と同じ位置に移動させることは，ルール上あり得ない
                 debug_assert_ne!(
                     Some(action.actor()),
-                    new_state.lattices[position_after_movement].map(|l| l.owner)
+                    state.geister_at(position_after_movement).map(|l| l.owner)
                 );
-                new_state.kill_geister_at(position_after_movement);
+                undo.killed_geister = state.geister_at(position_after_movement);
+                state.kill_geister_at(position_after_movement);
 
                 // 👻の移動
-                let moved_geister = new_state.lattices[action.target_geister_position];
-                new_state.lattices[position_after_movement] = moved_geister;
+                state.set_geister_at(position_after_movement, Some(moved_geister));
             }
             GeisterStateAfterAction::Clear => {
-                new_state.actor_of_cleared_geister = Some(action.actor());
+                state.set_actor_of_cleared_geister(Some(action.actor()));
             }
         }
         // 元々👻がいたマスには何もいなくなる
-        new_state.lattices[action.target_geister_position] = None;
+        state.set_geister_at(action.target_geister_position, None);
+        state.advance_ply();
 
-        new_state
+        undo
     }
-}
 
-/// 指定したエージェントの👻が上がれる位置を返す．
-pub fn clearable_position_of(actor: Actor) -> TableIndex {
-    match actor {
-        Actor::First => TableIndex::new(0, 0),
-        Actor::Second => FIELD_SIZE - TableIndex::new(1, 1),
+    /// `GeisterRule::apply()`で適用した行動を取り消し，適用前の状態に戻す．
+    /// 行動は適用した順と逆の順に取り消す必要がある．
+    pub fn undo(state: &mut GeisterState, undo: Undo) {
+        state.retract_ply();
+        state.set_actor_of_cleared_geister(undo.actor_of_cleared_geister);
+
+        // 移動先のマスを元に戻す
+        if let GeisterMovement::Direction(_) = undo.action.geister_movement {
+            let position_after_movement = undo
+                .action
+                .position_after_movement()
+                .expect("Undone action must have been available");
+            state.set_geister_at(position_after_movement, None);
+            if let Some(killed_geister) = undo.killed_geister {
+                state.revive_geister_at(position_after_movement, killed_geister);
+            }
+        }
+        // 行動した👻を元の位置に戻す
+        state.set_geister_at(
+            undo.action.target_geister_position,
+            Some(undo.moved_geister),
+        );
     }
 }
 
-/// 指定した位置にいる👻が指定した移動をした後の位置`position`を`Some(position)`として返す．
-/// ただし，👻が上がった場合は`Some(clear)`を返す．
-/// また，指定した行動がルール上取れない場合は`None`を返す．
+/// 指定した位置にいる👻が指定した移動をした後の位置`position`を`Ok(OnField(position))`として返す．
+/// ただし，👻が上がった場合は`Ok(Clear)`を返す．
+/// また，指定した行動がルール上取れない場合はその理由を`Err`として返す．
+/// ゲームが終了しているかどうかは判定しない．
 fn get_geister_state_after_action(
     state: &GeisterState,
     action: &GeisterAction,
-) -> Option<GeisterStateAfterAction> {
+) -> Result<GeisterStateAfterAction, IllegalAction> {
+    // 動かす👻が自分の👻であることを確かめる
+    if !state
+        .lattices()
+        .is_valid_index(action.target_geister_position)
+    {
+        return Err(IllegalAction::OffBoard);
+    }
+    let movement_geister = state
+        .geister_at(action.target_geister_position)
+        .ok_or(IllegalAction::NoGeisterAtSource)?;
+    if movement_geister.owner != action.actor() {
+        return Err(IllegalAction::OpponentGeister);
+    }
+
     match action.geister_movement {
-        GeisterMovement::Direction(d) => {
+        GeisterMovement::Direction(_) => {
             // 移動後の👻の位置を計算
-            let position_after_movement = {
-                let p = action.target_geister_position.try_cast::<isize>().ok()?;
-                let position_after_movement = p + d;
-                position_after_movement.try_cast().ok()?
-            };
-            // 移動後の位置がフィールドからはみ出ていたり，移動後の位置に自分の他の👻がいる場合は移動できない
-            if state.lattices.is_valid_index(position_after_movement)
-                && state.lattices[position_after_movement].map(|owned_geister| owned_geister.owner)
-                    != Some(action.actor())
+            let position_after_movement = action
+                .position_after_movement()
+                .filter(|&p| state.lattices().is_valid_index(p))
+                .ok_or(IllegalAction::OffBoard)?;
+            // 移動後の位置に自分の他の👻がいる場合は移動できない
+            if state
+                .geister_at(position_after_movement)
+                .map(|owned_geister| owned_geister.owner)
+                == Some(action.actor())
             {
-                Some(GeisterStateAfterAction::OnField(position_after_movement))
+                Err(IllegalAction::OwnGeisterOnTarget)
             } else {
-                None
+                Ok(GeisterStateAfterAction::OnField(position_after_movement))
             }
         }
         GeisterMovement::Clear => {
             // 移動対象の👻が善良な👻で，上がれる位置に存在するなら上がれる
-            let movement_geister = state.lattices[action.target_geister_position]
-                .expect("Geister must exit")
-                .geister;
-            if movement_geister == Geister::Holy
-                && action.target_geister_position == clearable_position_of(action.actor())
+            if movement_geister.geister != Geister::Holy {
+                Err(IllegalAction::ClearingEvilGeister)
+            } else if !state
+                .rules()
+                .clearable_positions_of(action.actor())
+                .contains(&action.target_geister_position)
             {
-                Some(GeisterStateAfterAction::Clear)
+                Err(IllegalAction::ClearingFromNonExit)
             } else {
-                None
+                Ok(GeisterStateAfterAction::Clear)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geisters, GeisterRules, InitialPlacement, RandomPlacement};
+    use data_structure::TableSize;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    /// ルールごとに試す対局の数．
+    const GAME_COUNT: usize = 20;
+
+    /// 指定したルールで，ランダムな初期配置から始まる状態を返す．
+    fn random_initial_state(rules: GeisterRules, rng: &mut StdRng) -> GeisterState {
+        let first_setup = RandomPlacement.select_setup(&rules, Actor::First, rng);
+        let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, rng);
+        GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap()
+    }
+
+    /// 指定した状態と同じ要素から，差分更新によらずに状態を作り直す．
+    fn rebuild(state: &GeisterState) -> GeisterState {
+        let mut killed_geister_counts = HashMap::new();
+        for &geister in geisters().iter() {
+            for &actor in actors().iter() {
+                let owned_geister = OwnedGeister::new(geister, actor);
+                killed_geister_counts
+                    .insert(owned_geister, state.killed_geister_count(owned_geister));
+            }
+        }
+        GeisterState::from_parts(
+            *state.rules(),
+            state.lattices().clone(),
+            killed_geister_counts,
+            state.actor_of_cleared_geister(),
+            state.ply(),
+        )
+    }
+
+    /// 2つの状態が，手数や差分更新したハッシュも含めて等しいことを確かめる．
+    fn assert_identical(expected: &GeisterState, actual: &GeisterState) {
+        assert_eq!(expected, actual);
+        assert_eq!(expected.ply(), actual.ply());
+        assert_eq!(
+            expected.actor_of_cleared_geister(),
+            actual.actor_of_cleared_geister()
+        );
+        for &geister in geisters().iter() {
+            for &actor in actors().iter() {
+                let owned_geister = OwnedGeister::new(geister, actor);
+                assert_eq!(
+                    expected.killed_geister_count(owned_geister),
+                    actual.killed_geister_count(owned_geister)
+                );
+            }
+        }
+        assert_eq!(expected.zobrist_hash(), actual.zobrist_hash());
+        assert_eq!(rebuild(actual).zobrist_hash(), actual.zobrist_hash());
+    }
+
+    #[test]
+    fn validate_reports_why_action_is_illegal() {
+        let state = "1hhhh1/1eeee1/6/6/1EEEE1/1HHHH1 f 0,0,0,0 - 0 6x6:4:4"
+            .parse::<GeisterState>()
+            .unwrap();
+        let forward = GeisterMovement::Direction(Pair::new(0, -1));
+        let backward = GeisterMovement::Direction(Pair::new(0, 1));
+        let validate = |x, y, movement, actor| {
+            GeisterRule::validate(
+                &state,
+                &GeisterAction::new(TableIndex::new(x, y), movement, actor),
+            )
+        };
+
+        assert_eq!(Ok(()), validate(1, 4, forward, Actor::First));
+        assert_eq!(
+            Err(IllegalAction::NotYourTurn),
+            validate(1, 1, backward, Actor::Second)
+        );
+        assert_eq!(
+            Err(IllegalAction::NoGeisterAtSource),
+            validate(0, 5, forward, Actor::First)
+        );
+        assert_eq!(
+            Err(IllegalAction::OpponentGeister),
+            validate(1, 1, forward, Actor::First)
+        );
+        assert_eq!(
+            Err(IllegalAction::OffBoard),
+            validate(1, 5, backward, Actor::First)
+        );
+        assert_eq!(
+            Err(IllegalAction::OffBoard),
+            validate(6, 5, forward, Actor::First)
+        );
+        assert_eq!(
+            Err(IllegalAction::OwnGeisterOnTarget),
+            validate(1, 5, forward, Actor::First)
+        );
+        assert_eq!(
+            Err(IllegalAction::ClearingEvilGeister),
+            validate(1, 4, GeisterMovement::Clear, Actor::First)
+        );
+        assert_eq!(
+            Err(IllegalAction::ClearingFromNonExit),
+            validate(1, 5, GeisterMovement::Clear, Actor::First)
+        );
+    }
+
+    #[test]
+    fn validate_accepts_clearing_from_exit_and_rejects_after_game_over() {
+        let mut state = "Hhhhh1/1eeee1/6/6/1EEEE1/1HHH2 f 0,0,0,0 - 0 6x6:4:4"
+            .parse::<GeisterState>()
+            .unwrap();
+        let clear = GeisterAction::new(TableIndex::new(0, 0), GeisterMovement::Clear, Actor::First);
+        assert_eq!(Ok(()), GeisterRule::validate(&state, &clear));
+        GeisterRule::apply(&mut state, &clear);
+
+        // 終局後は，手番のプレイヤーの行動もとれない
+        let action = GeisterAction::new(
+            TableIndex::new(1, 1),
+            GeisterMovement::Direction(Pair::new(0, 1)),
+            Actor::Second,
+        );
+        assert_eq!(
+            Err(IllegalAction::GameOver),
+            GeisterRule::validate(&state, &action)
+        );
+    }
+
+    #[test]
+    fn outcome_of_is_draw_at_max_ply() {
+        // 手数の上限がなければ，同じ手を指しても終局しない
+        for &max_ply in [Some(4), None].iter() {
+            let mut rng = StdRng::seed_from_u64(3);
+            let rules = GeisterRules::standard().with_max_ply(max_ply);
+            let mut state = random_initial_state(rules, &mut rng);
+            for ply in 0..4 {
+                assert_eq!(ply, state.ply());
+                assert_eq!(None, GeisterRule::outcome_of(&state));
+                // 序盤の4手では👻を取れないため，手数の上限より先に終局することはない
+                let actions = GeisterRule::iterate_available_actions(&state, state.current_actor());
+                GeisterRule::apply(&mut state, actions.choose(&mut rng).unwrap());
+            }
+            let expected = max_ply.map(|_| GameOutcome::Draw {
+                reason: OutcomeReason::MoveLimit,
+            });
+            assert_eq!(expected, GeisterRule::outcome_of(&state));
+            assert_eq!(max_ply.is_some(), GeisterRule::is_game_over(&state));
+        }
+    }
+
+    #[test]
+    fn undo_restores_state_over_random_games() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let rules_list = vec![
+            GeisterRules::standard(),
+            GeisterRules::with_field_size(TableSize::new(4, 4)).unwrap(),
+            GeisterRules::with_field_size(TableSize::new(8, 8)).unwrap(),
+        ];
+        for rules in rules_list {
+            for _ in 0..GAME_COUNT {
+                let mut state = random_initial_state(rules.with_max_ply(Some(200)), &mut rng);
+                let mut history = vec![];
+                while !GeisterRule::is_game_over(&state) {
+                    let actor = state.current_actor();
+                    let actions = GeisterRule::iterate_available_actions(&state, actor);
+                    assert_eq!(
+                        actions.len(),
+                        GeisterRule::count_legal_actions(&state, actor)
+                    );
+                    assert_eq!(
+                        !actions.is_empty(),
+                        GeisterRule::has_any_legal_action(&state, actor)
+                    );
+                    let action = match actions.choose(&mut rng) {
+                        Some(&action) => action,
+                        None => break,
+                    };
+                    let snapshot = state.clone();
+                    let undo = GeisterRule::apply(&mut state, &action);
+                    history.push((snapshot, undo));
+                }
+
+                // 適用した順と逆の順に取り消すと，各手の適用前の状態に戻る
+                while let Some((snapshot, undo)) = history.pop() {
+                    GeisterRule::undo(&mut state, undo);
+                    assert_identical(&snapshot, &state);
+                }
             }
         }
     }
@@ -0,0 +1,329 @@
+use crate::Geister;
+use data_structure::{TableIndex, TableSize};
+use minimax_strategy::Actor;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// ゲームGeisterの盤面の大きさや👻の数などの取り決めを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GeisterRules {
+    /// ゲームフィールドの大きさ．
+    field_size: TableSize,
+    /// ゲーム開始時に各プレイヤーがフィールドに置く善良な👻の数．
+    holy_count: usize,
+    /// ゲーム開始時に各プレイヤーがフィールドに置く邪悪な👻の数．
+    evil_count: usize,
+    /// 初期配置に使える，各プレイヤー側の行数．
+    setup_row_count: usize,
+    /// 引き分けとなる手数．`None`の場合は手数の上限を設けない．
+    max_ply: Option<usize>,
+}
+
+/// ルールの組み合わせが不正であることを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulesError {
+    /// フィールドが小さすぎて，脱出マスや初期配置の領域を確保できない．
+    FieldTooSmall(TableSize),
+    /// フィールドの列または行が`GeisterRules::MAX_FIELD_LENGTH`を超えている．
+    FieldTooLarge(TableSize),
+    /// 各色の👻は一体以上必要．
+    NoGeister(Geister),
+    /// 初期配置の領域に，すべての👻を置けない．
+    SetupZoneTooSmall { capacity: usize, required: usize },
+}
+
+impl GeisterRules {
+    /// 初期配置に使える，各プレイヤー側の行数の既定値．
+    pub const DEFAULT_SETUP_ROW_COUNT: usize = 2;
+    /// フィールドの列と行の数の上限．列を`a`から`z`の1文字で表記できる数とする．
+    pub const MAX_FIELD_LENGTH: usize = 26;
+    /// 対局で手数の上限を指定しなかった場合に使う，引き分けとなる手数．
+    pub const DEFAULT_MAX_PLY: usize = 300;
+
+    /// 指定した取り決めのルールを生成する．
+    pub fn new(
+        field_size: TableSize,
+        holy_count: usize,
+        evil_count: usize,
+        setup_row_count: usize,
+    ) -> Result<Self, RulesError> {
+        if field_size.x > Self::MAX_FIELD_LENGTH || field_size.y > Self::MAX_FIELD_LENGTH {
+            return Err(RulesError::FieldTooLarge(field_size));
+        }
+        // 両隅の脱出マスと初期配置の列を確保するには3列以上，
+        // 両プレイヤーの初期配置の領域が重ならないためには行数の2倍以上の行が必要
+        let is_zone_separated = setup_row_count
+            .checked_mul(2)
+            .is_some_and(|row_count| row_count <= field_size.y);
+        if field_size.x < 3 || setup_row_count == 0 || !is_zone_separated {
+            return Err(RulesError::FieldTooSmall(field_size));
+        }
+        if holy_count == 0 {
+            return Err(RulesError::NoGeister(Geister::Holy));
+        }
+        if evil_count == 0 {
+            return Err(RulesError::NoGeister(Geister::Evil));
+        }
+
+        let rules = Self {
+            field_size,
+            holy_count,
+            evil_count,
+            setup_row_count,
+            max_ply: None,
+        };
+        let capacity = rules.setup_zone_capacity();
+        let required = holy_count + evil_count;
+        if capacity < required {
+            return Err(RulesError::SetupZoneTooSmall { capacity, required });
+        }
+
+        Ok(rules)
+    }
+
+    /// 6x6のフィールドで，各プレイヤーが善良な👻と邪悪な👻を4体ずつ持つ標準ルールを返す．
+    pub fn standard() -> Self {
+        Self::with_field_size(TableSize::new(6, 6)).expect("Standard rules must be valid")
+    }
+
+    /// 指定した大きさのフィールドで，初期配置の領域をちょうど埋める数の👻を使うルールを返す．
+    pub fn with_field_size(field_size: TableSize) -> Result<Self, RulesError> {
+        Self::with_setup_zone(field_size, Self::DEFAULT_SETUP_ROW_COUNT)
+    }
+
+    /// 指定した大きさのフィールドと初期配置の行数で，
+    /// 初期配置の領域を善良な👻と邪悪な👻で半分ずつ埋めるルールを返す．
+    /// 領域のマスの数が奇数の場合は，1マス空ける．
+    pub fn with_setup_zone(
+        field_size: TableSize,
+        setup_row_count: usize,
+    ) -> Result<Self, RulesError> {
+        let count = field_size
+            .x
+            .saturating_sub(2)
+            .checked_mul(setup_row_count)
+            .ok_or(RulesError::FieldTooSmall(field_size))?
+            / 2;
+        Self::new(field_size, count, count, setup_row_count)
+    }
+
+    /// 指定した手数で引き分けとなるルールを返す．
+    /// `None`を指定した場合は手数の上限を設けない．
+    pub fn with_max_ply(self, max_ply: Option<usize>) -> Self {
+        Self { max_ply, ..self }
+    }
+
+    /// 引き分けとなる手数`ply`を`Some(ply)`として返す．
+    /// 手数の上限がない場合は`None`を返す．
+    pub fn max_ply(&self) -> Option<usize> {
+        self.max_ply
+    }
+
+    /// ゲームフィールドの大きさを返す．
+    pub fn field_size(&self) -> TableSize {
+        self.field_size
+    }
+
+    /// 初期配置に使える，各プレイヤー側の行数を返す．
+    pub fn setup_row_count(&self) -> usize {
+        self.setup_row_count
+    }
+
+    /// ゲーム開始時に各プレイヤーがフィールドに置く，指定した種類の👻の数を返す．
+    pub fn initial_geister_count(&self, geister: Geister) -> usize {
+        match geister {
+            Geister::Holy => self.holy_count,
+            Geister::Evil => self.evil_count,
+        }
+    }
+
+    /// 指定したプレイヤーが👻を初期配置できる位置を返す．
+    /// 各プレイヤー，最も自分側よりの行で，かつ端以外の列にあるマスに👻を配置できる．
+    pub fn setup_positions_of(&self, actor: Actor) -> Vec<TableIndex> {
+        let y_range = match actor {
+            Actor::First => self.field_size.y - self.setup_row_count..self.field_size.y,
+            Actor::Second => 0..self.setup_row_count,
+        };
+        y_range
+            .flat_map(|y| (1..self.field_size.x - 1).map(move |x| TableIndex::new(x, y)))
+            .collect()
+    }
+
+    /// 指定したエージェントの👻が上がれる位置を返す．
+    /// 👻は相手側の最奥の行の両隅から上がれる．
+    pub fn clearable_positions_of(&self, actor: Actor) -> [TableIndex; 2] {
+        let y = match actor {
+            Actor::First => 0,
+            Actor::Second => self.field_size.y - 1,
+        };
+        [
+            TableIndex::new(0, y),
+            TableIndex::new(self.field_size.x - 1, y),
+        ]
+    }
+
+    /// 各プレイヤーの初期配置の領域にあるマスの数を返す．
+    fn setup_zone_capacity(&self) -> usize {
+        (self.field_size.x - 2) * self.setup_row_count
+    }
+}
+
+impl Default for GeisterRules {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// `6x6:4:4`のように，フィールドの大きさと善良な👻・邪悪な👻の数を表記する．
+/// 初期配置の行数が既定値と異なる場合は`8x8/3:9:9`のようにフィールドの大きさの後に付け加える．
+/// 手数の上限がある場合は`6x6:4:4:300`のように末尾に付け加える．
+impl Display for GeisterRules {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.field_size.x, self.field_size.y)?;
+        if self.setup_row_count != Self::DEFAULT_SETUP_ROW_COUNT {
+            write!(f, "/{}", self.setup_row_count)?;
+        }
+        write!(f, ":{}:{}", self.holy_count, self.evil_count)?;
+        if let Some(max_ply) = self.max_ply {
+            write!(f, ":{}", max_ply)?;
+        }
+        Ok(())
+    }
+}
+
+/// `6x6`や`8x8:5:5`，`8x8/3:9:9`，`6x6:4:4:300`のような表記からルールを生成する．
+/// 初期配置の行数を省略した場合は既定値を使う．
+/// 👻の数を省略した場合は，初期配置の領域を半分ずつ埋める数の👻を使う．
+impl FromStr for GeisterRules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split(':');
+        let parse_count = |count: &str| {
+            count
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid count: {}", count))
+        };
+        let (field_size, setup_row_count) = {
+            let zone = fields.next().unwrap_or_default();
+            let mut parts = zone.split('/');
+            let size = parts.next().unwrap_or_default();
+            let mut lengths = size.split('x').map(|l| l.parse::<usize>());
+            let field_size = match (lengths.next(), lengths.next(), lengths.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => TableSize::new(x, y),
+                _ => return Err(format!("Invalid field size: {}", size)),
+            };
+            let setup_row_count = match (parts.next(), parts.next()) {
+                (None, _) => Self::DEFAULT_SETUP_ROW_COUNT,
+                (Some(row_count), None) => parse_count(row_count)?,
+                _ => return Err(format!("Invalid setup zone: {}", zone)),
+            };
+            (field_size, setup_row_count)
+        };
+
+        let (rules, max_ply) = match (fields.next(), fields.next(), fields.next()) {
+            (None, _, _) => (Self::with_setup_zone(field_size, setup_row_count), None),
+            (Some(holy_count), Some(evil_count), max_ply) => (
+                Self::new(
+                    field_size,
+                    parse_count(holy_count)?,
+                    parse_count(evil_count)?,
+                    setup_row_count,
+                ),
+                max_ply.map(parse_count).transpose()?,
+            ),
+            _ => return Err(format!("Invalid rules: {}", s)),
+        };
+        if fields.next().is_some() {
+            return Err(format!("Invalid rules: {}", s));
+        }
+        rules
+            .map(|rules| rules.with_max_ply(max_ply))
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Display for RulesError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RulesError::FieldTooSmall(size) => {
+                write!(f, "Field {}x{} is too small", size.x, size.y)
+            }
+            RulesError::FieldTooLarge(size) => write!(
+                f,
+                "Field {}x{} is too large; at most {} columns and rows are allowed",
+                size.x,
+                size.y,
+                GeisterRules::MAX_FIELD_LENGTH
+            ),
+            RulesError::NoGeister(geister) => {
+                write!(f, "At least one {:?} geister is required", geister)
+            }
+            RulesError::SetupZoneTooSmall { capacity, required } => write!(
+                f,
+                "Setup zone has only {} squares but {} geisters are required",
+                capacity, required
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        let rules_list = vec![
+            GeisterRules::standard(),
+            GeisterRules::standard().with_max_ply(Some(300)),
+            GeisterRules::new(TableSize::new(4, 4), 1, 2, 2).unwrap(),
+            GeisterRules::new(TableSize::new(8, 8), 9, 9, 3).unwrap(),
+            GeisterRules::new(TableSize::new(8, 8), 3, 3, 1)
+                .unwrap()
+                .with_max_ply(Some(100)),
+        ];
+        for rules in rules_list {
+            let notation = rules.to_string();
+            assert_eq!(Ok(rules), notation.parse::<GeisterRules>(), "{}", notation);
+        }
+    }
+
+    #[test]
+    fn from_str_keeps_default_notation() {
+        assert_eq!("6x6:4:4", GeisterRules::standard().to_string());
+        assert_eq!(Ok(GeisterRules::standard()), "6x6".parse());
+        assert_eq!(
+            "8x8/3:9:9",
+            "8x8/3".parse::<GeisterRules>().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn from_str_rejects_invalid_setup_zone() {
+        assert!("6x6/:4:4".parse::<GeisterRules>().is_err());
+        assert!("6x6/2/3:4:4".parse::<GeisterRules>().is_err());
+        assert!("6x6/4:4:4".parse::<GeisterRules>().is_err());
+        assert!("6x6/9223372036854775808".parse::<GeisterRules>().is_err());
+        assert!("6x6/9223372036854775808:4:4"
+            .parse::<GeisterRules>()
+            .is_err());
+    }
+
+    #[test]
+    fn new_rejects_too_large_field() {
+        let max = GeisterRules::MAX_FIELD_LENGTH;
+        assert!(GeisterRules::with_field_size(TableSize::new(max, max)).is_ok());
+        assert_eq!(
+            Err(RulesError::FieldTooLarge(TableSize::new(max + 1, 6))),
+            GeisterRules::with_field_size(TableSize::new(max + 1, 6))
+        );
+        assert_eq!(
+            Err(RulesError::FieldTooLarge(TableSize::new(6, max + 1))),
+            GeisterRules::with_field_size(TableSize::new(6, max + 1))
+        );
+        assert!("100000x100000:1:1".parse::<GeisterRules>().is_err());
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{geisters, Geister, GeisterRules, OwnedGeister};
+use data_structure::TableIndex;
+use minimax_strategy::Actor;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// あるプレイヤーの👻の初期配置．
+/// `place()`で👻をひとつずつ置いて組み立てる．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    /// 👻の所有者．
+    actor: Actor,
+    /// 👻を置く位置と，その👻の種類．
+    geisters: HashMap<TableIndex, Geister>,
+}
+
+/// 初期配置がルールに合わないことを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupError {
+    /// 同じマスに複数の👻を置こうとした．
+    OverlappingPosition(TableIndex),
+    /// 初期配置の領域の外に👻を置こうとした．
+    OutsideSetupZone { actor: Actor, position: TableIndex },
+    /// ある種類の👻が一体も置かれていない．
+    MissingGeister(OwnedGeister),
+    /// ある種類の👻の数がルールと合わない．
+    WrongGeisterCount {
+        owned_geister: OwnedGeister,
+        expected: usize,
+        actual: usize,
+    },
+    /// 初期配置の所有者が想定と異なる．
+    WrongActor { expected: Actor, actual: Actor },
+}
+
+impl Setup {
+    /// 指定したプレイヤーの，👻がひとつも置かれていない初期配置を生成する．
+    pub fn new(actor: Actor) -> Self {
+        Self {
+            actor,
+            geisters: HashMap::new(),
+        }
+    }
+
+    /// 👻の所有者を返す．
+    pub fn actor(&self) -> Actor {
+        self.actor
+    }
+
+    /// 指定した位置に指定した種類の👻を置く．
+    /// その位置に既に👻が置かれている場合はエラーを返す．
+    pub fn place(
+        &mut self,
+        position: TableIndex,
+        geister: Geister,
+    ) -> Result<&mut Self, SetupError> {
+        if self.geisters.contains_key(&position) {
+            return Err(SetupError::OverlappingPosition(position));
+        }
+        self.geisters.insert(position, geister);
+        Ok(self)
+    }
+
+    /// 指定した位置に置かれた👻の種類を返す．
+    pub fn geister_at(&self, position: TableIndex) -> Option<Geister> {
+        self.geisters.get(&position).cloned()
+    }
+
+    /// 指定した二つの位置にいる👻を入れ替える．
+    /// 片方の位置にしか👻がいない場合は，その👻をもう片方の位置へ移す．
+    pub fn swap(&mut self, a: TableIndex, b: TableIndex) {
+        let geister_a = self.geisters.remove(&a);
+        let geister_b = self.geisters.remove(&b);
+        if let Some(geister) = geister_a {
+            self.geisters.insert(b, geister);
+        }
+        if let Some(geister) = geister_b {
+            self.geisters.insert(a, geister);
+        }
+    }
+
+    /// 置かれたすべての👻の位置と種類を列挙する．
+    pub fn iter(&self) -> impl Iterator<Item = (TableIndex, Geister)> + '_ {
+        self.geisters
+            .iter()
+            .map(|(&position, &geister)| (position, geister))
+    }
+
+    /// 初期配置が指定したルールに合っているか確かめる．
+    pub fn validate(&self, rules: &GeisterRules) -> Result<(), SetupError> {
+        let setup_positions = rules.setup_positions_of(self.actor);
+        if let Some(&position) = self
+            .geisters
+            .keys()
+            .find(|position| !setup_positions.contains(position))
+        {
+            return Err(SetupError::OutsideSetupZone {
+                actor: self.actor,
+                position,
+            });
+        }
+
+        for &geister in geisters().iter() {
+            let owned_geister = OwnedGeister::new(geister, self.actor);
+            let expected = rules.initial_geister_count(geister);
+            let actual = self.geisters.values().filter(|&&g| g == geister).count();
+            if actual == 0 {
+                return Err(SetupError::MissingGeister(owned_geister));
+            } else if actual != expected {
+                return Err(SetupError::WrongGeisterCount {
+                    owned_geister,
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for SetupError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SetupError::OverlappingPosition(p) => {
+                write!(f, "Two geisters are placed at ({}, {})", p.x, p.y)
+            }
+            SetupError::OutsideSetupZone { actor, position } => write!(
+                f,
+                "({}, {}) is outside the setup zone of {:?}",
+                position.x, position.y, actor
+            ),
+            SetupError::MissingGeister(owned_geister) => write!(
+                f,
+                "{:?} has no {:?} geister",
+                owned_geister.owner, owned_geister.geister
+            ),
+            SetupError::WrongGeisterCount {
+                owned_geister,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{:?} has {} {:?} geisters but {} are required",
+                owned_geister.owner, actual, owned_geister.geister, expected
+            ),
+            SetupError::WrongActor { expected, actual } => {
+                write!(f, "Setup of {:?} is given for {:?}", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SetupError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GeisterState;
+    use data_structure::TableSize;
+
+    /// 善良な👻を最も後ろの行に，邪悪な👻をその前の行に並べた初期配置を返す．
+    fn setup_of(rules: &GeisterRules, actor: Actor) -> Setup {
+        let size = rules.field_size();
+        let (holy_y, evil_y) = match actor {
+            Actor::First => (size.y - 1, size.y - 2),
+            Actor::Second => (0, 1),
+        };
+        let mut setup = Setup::new(actor);
+        for x in 1..size.x - 1 {
+            setup
+                .place(TableIndex::new(x, holy_y), Geister::Holy)
+                .unwrap()
+                .place(TableIndex::new(x, evil_y), Geister::Evil)
+                .unwrap();
+        }
+        setup
+    }
+
+    #[test]
+    fn place_and_swap_move_geisters() {
+        let a = TableIndex::new(1, 5);
+        let b = TableIndex::new(2, 5);
+        let c = TableIndex::new(3, 5);
+        let mut setup = Setup::new(Actor::First);
+        setup
+            .place(a, Geister::Holy)
+            .unwrap()
+            .place(b, Geister::Evil)
+            .unwrap();
+        assert_eq!(Actor::First, setup.actor());
+        assert_eq!(
+            Err(SetupError::OverlappingPosition(a)),
+            setup.place(a, Geister::Evil).map(|_| ())
+        );
+        assert_eq!(Some(Geister::Holy), setup.geister_at(a));
+
+        // 👻同士を入れ替える
+        setup.swap(a, b);
+        assert_eq!(Some(Geister::Evil), setup.geister_at(a));
+        assert_eq!(Some(Geister::Holy), setup.geister_at(b));
+
+        // 空きマスとの入れ替えは移動になる
+        setup.swap(c, a);
+        assert_eq!(None, setup.geister_at(a));
+        assert_eq!(Some(Geister::Evil), setup.geister_at(c));
+        assert_eq!(2, setup.iter().count());
+    }
+
+    #[test]
+    fn validate_accepts_filled_setup_zone() {
+        let rules = GeisterRules::standard();
+        let first_setup = setup_of(&rules, Actor::First);
+        let second_setup = setup_of(&rules, Actor::Second);
+        assert_eq!(Ok(()), first_setup.validate(&rules));
+        assert_eq!(Ok(()), second_setup.validate(&rules));
+
+        let state = GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+        let expected = format!("1hhhh1/1eeee1/6/6/1EEEE1/1HHHH1 f 0,0,0,0 - 0 {}", rules)
+            .parse::<GeisterState>()
+            .unwrap();
+        assert_eq!(expected, state);
+    }
+
+    #[test]
+    fn validate_reports_why_setup_is_invalid() {
+        let rules = GeisterRules::standard();
+        let owned_geister = |geister| OwnedGeister::new(geister, Actor::First);
+
+        // 初期配置の領域の外に置いた
+        let mut setup = setup_of(&rules, Actor::First);
+        setup.swap(TableIndex::new(1, 4), TableIndex::new(1, 3));
+        assert_eq!(
+            Err(SetupError::OutsideSetupZone {
+                actor: Actor::First,
+                position: TableIndex::new(1, 3)
+            }),
+            setup.validate(&rules)
+        );
+
+        // 後手の初期配置の領域に置いた
+        let mut setup = setup_of(&rules, Actor::First);
+        setup.swap(TableIndex::new(1, 4), TableIndex::new(1, 1));
+        assert_eq!(
+            Err(SetupError::OutsideSetupZone {
+                actor: Actor::First,
+                position: TableIndex::new(1, 1)
+            }),
+            setup.validate(&rules)
+        );
+
+        // 邪悪な👻が一体少ない
+        let mut setup = Setup::new(Actor::First);
+        for x in 1..5 {
+            setup.place(TableIndex::new(x, 5), Geister::Holy).unwrap();
+        }
+        for x in 1..4 {
+            setup.place(TableIndex::new(x, 4), Geister::Evil).unwrap();
+        }
+        assert_eq!(
+            Err(SetupError::WrongGeisterCount {
+                owned_geister: owned_geister(Geister::Evil),
+                expected: 4,
+                actual: 3
+            }),
+            setup.validate(&rules)
+        );
+
+        // 善良な👻が多すぎる
+        let three_geister_rules = GeisterRules::new(TableSize::new(6, 6), 3, 3, 2).unwrap();
+        assert_eq!(
+            Err(SetupError::WrongGeisterCount {
+                owned_geister: owned_geister(Geister::Holy),
+                expected: 3,
+                actual: 4
+            }),
+            setup.validate(&three_geister_rules)
+        );
+
+        // 邪悪な👻がいない
+        let mut setup = Setup::new(Actor::First);
+        for x in 1..5 {
+            setup.place(TableIndex::new(x, 5), Geister::Holy).unwrap();
+        }
+        assert_eq!(
+            Err(SetupError::MissingGeister(owned_geister(Geister::Evil))),
+            setup.validate(&rules)
+        );
+    }
+
+    #[test]
+    fn create_initial_state_rejects_setup_of_wrong_actor() {
+        let rules = GeisterRules::standard();
+        let second_setup = setup_of(&rules, Actor::Second);
+        assert_eq!(
+            Err(SetupError::WrongActor {
+                expected: Actor::First,
+                actual: Actor::Second
+            }),
+            GeisterState::create_initial_state(rules, &second_setup, &second_setup)
+        );
+    }
+}
@@ -1,12 +1,9 @@
-use data_structure::{Table, TableIndex, TableSize};
+use crate::{zobrist, GeisterRule, GeisterRules, Setup, SetupError};
+use data_structure::{Table, TableIndex};
 use minimax_strategy::{actors, Actor, State};
 use std::collections::HashMap;
-
-/// ゲームフィールドの大きさ．
-pub const FIELD_SIZE: TableSize = TableSize::new(6, 6);
-
-/// ゲーム開始時にフィールドに存在する👻の数．
-pub const INITIAL_GEISTER_COUNT: usize = 4;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
 
 /// ゲームフィールドに登場する駒(👻)の種類．
 /// プレイヤーはこの駒を操作してゲームを進めていく．
@@ -34,30 +31,49 @@ impl OwnedGeister {
 }
 
 /// ゲームGeisterの状態．
+///
+/// 2つの状態は，フィールド上の👻，取り除かれた👻の数，上がった👻の所有者，手番が
+/// すべて等しいときに等しいとみなす．手数そのものは比較しないため，
+/// 異なる手数で同じ局面に至った状態どうしも等しくなる．
 #[derive(Clone)]
 pub struct GeisterState {
+    /// このゲームのルール．
+    rules: GeisterRules,
     /// フィールドに存在する👻．
-    pub lattices: Table<Option<OwnedGeister>>,
+    lattices: Table<Option<OwnedGeister>>,
     /// フィールドから取り除かれた👻の数．
     killed_geister_counts: HashMap<OwnedGeister, usize>,
     /// フィールドから上がった👻の所有者．
     /// これが`None`でないということは，ゲームが終了したことを表す．
-    pub actor_of_cleared_geister: Option<Actor>,
+    actor_of_cleared_geister: Option<Actor>,
+    /// ゲーム開始から進んだ手数．
+    ply: usize,
+    /// 局面のZobristハッシュ．状態を変更するたびに差分更新する．
+    zobrist_hash: u64,
 }
 
 impl GeisterState {
-    /// 指定した初期配置をもとに，初期状態を生成する．
+    /// 指定したルールと各プレイヤーの初期配置をもとに，初期状態を生成する．
+    /// 初期配置がルールに合わない場合はエラーを返す．
     pub fn create_initial_state(
-        initial_geister_positions: HashMap<OwnedGeister, Vec<TableIndex>>,
-    ) -> Self {
+        rules: GeisterRules,
+        first_setup: &Setup,
+        second_setup: &Setup,
+    ) -> Result<Self, SetupError> {
         // フィールドに👻を配置
-        let mut lattices = Table::from_fill(None, FIELD_SIZE);
-        for (owned_geister, positions) in initial_geister_positions {
-            // 👻の数が想定と合っているか確かめる
-            assert_eq!(INITIAL_GEISTER_COUNT, positions.len());
+        let mut lattices = Table::from_fill(None, rules.field_size());
+        for (&actor, setup) in actors().iter().zip([first_setup, second_setup].iter()) {
+            // 初期配置がルールに合っているか確かめる
+            if setup.actor() != actor {
+                return Err(SetupError::WrongActor {
+                    expected: actor,
+                    actual: setup.actor(),
+                });
+            }
+            setup.validate(&rules)?;
 
-            for &position in positions.iter() {
-                lattices[position] = Some(owned_geister);
+            for (position, geister) in setup.iter() {
+                lattices[position] = Some(OwnedGeister::new(geister, actor));
             }
         }
 
@@ -70,11 +86,106 @@ impl GeisterState {
             }
         }
 
-        Self {
+        Ok(Self::from_parts(
+            rules,
+            lattices,
+            killed_geister_counts,
+            None,
+            0,
+        ))
+    }
+
+    /// 状態を構成する要素から状態を生成する．
+    /// 各要素の整合性は呼び出し側が保証する必要がある．
+    pub fn from_parts(
+        rules: GeisterRules,
+        lattices: Table<Option<OwnedGeister>>,
+        killed_geister_counts: HashMap<OwnedGeister, usize>,
+        actor_of_cleared_geister: Option<Actor>,
+        ply: usize,
+    ) -> Self {
+        let mut state = Self {
+            rules,
             lattices,
             killed_geister_counts,
-            actor_of_cleared_geister: None,
+            actor_of_cleared_geister,
+            ply,
+            zobrist_hash: 0,
+        };
+        state.zobrist_hash = state.compute_zobrist_hash();
+        state
+    }
+
+    /// このゲームのルールを返す．
+    pub fn rules(&self) -> &GeisterRules {
+        &self.rules
+    }
+
+    /// フィールドに存在する👻を返す．
+    pub fn lattices(&self) -> &Table<Option<OwnedGeister>> {
+        &self.lattices
+    }
+
+    /// 指定した位置にいる👻を返す．
+    pub fn geister_at(&self, position: TableIndex) -> Option<OwnedGeister> {
+        self.lattices[position]
+    }
+
+    /// 指定した位置にいる👻を置き換える．
+    /// 取り除かれた👻の数は変わらない．
+    pub fn set_geister_at(&mut self, position: TableIndex, owned_geister: Option<OwnedGeister>) {
+        if let Some(previous_geister) = self.lattices[position] {
+            self.zobrist_hash ^= zobrist::geister_key(position, previous_geister);
         }
+        if let Some(owned_geister) = owned_geister {
+            self.zobrist_hash ^= zobrist::geister_key(position, owned_geister);
+        }
+        self.lattices[position] = owned_geister;
+    }
+
+    /// フィールドから上がった👻の所有者`actor`を`Some(actor)`として返す．
+    /// これが`None`でないということは，ゲームが終了したことを表す．
+    pub fn actor_of_cleared_geister(&self) -> Option<Actor> {
+        self.actor_of_cleared_geister
+    }
+
+    /// フィールドから上がった👻の所有者を設定する．
+    pub fn set_actor_of_cleared_geister(&mut self, actor: Option<Actor>) {
+        if let Some(previous_actor) = self.actor_of_cleared_geister {
+            self.zobrist_hash ^= zobrist::cleared_key(previous_actor);
+        }
+        if let Some(actor) = actor {
+            self.zobrist_hash ^= zobrist::cleared_key(actor);
+        }
+        self.actor_of_cleared_geister = actor;
+    }
+
+    /// 局面のZobristハッシュを返す．
+    /// フィールド上の👻，上がった👻の所有者，手番から計算され，手数によらない．
+    pub fn zobrist_hash(&self) -> u64 {
+        self.zobrist_hash
+    }
+
+    /// ゲーム開始から進んだ手数を返す．
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// 手番のプレイヤーを返す．先手から交互に行動する．
+    pub fn current_actor(&self) -> Actor {
+        GeisterRule::actor_on_ply(self.ply)
+    }
+
+    /// 手数をひとつ進める．
+    pub fn advance_ply(&mut self) {
+        self.ply += 1;
+        self.zobrist_hash ^= zobrist::second_to_move_key();
+    }
+
+    /// 手数をひとつ戻す．
+    pub fn retract_ply(&mut self) {
+        self.ply -= 1;
+        self.zobrist_hash ^= zobrist::second_to_move_key();
     }
 
     /// 指定した👻が何体フィールドから取り除かれたか返す．
@@ -87,7 +198,7 @@ impl GeisterState {
     pub fn kill_geister_at(&mut self, position: TableIndex) {
         if let Some(killed_owned_geister) = self.lattices[position] {
             // 元々👻がいたところにはもう何もない
-            self.lattices[position] = None;
+            self.set_geister_at(position, None);
             // 取り除かれた👻の数を更新
             *self
                 .killed_geister_counts
@@ -95,6 +206,79 @@ impl GeisterState {
                 .expect("key (OwnedGeister) must exist") += 1;
         }
     }
+
+    /// フィールドから取り除かれた👻を指定した位置に戻し，取り除かれた👻のカウントを更新する．
+    /// `kill_geister_at()`の逆の操作である．
+    pub fn revive_geister_at(&mut self, position: TableIndex, owned_geister: OwnedGeister) {
+        debug_assert!(self.lattices[position].is_none());
+        self.set_geister_at(position, Some(owned_geister));
+        *self
+            .killed_geister_counts
+            .get_mut(&owned_geister)
+            .expect("key (OwnedGeister) must exist") -= 1;
+    }
+
+    /// Zobristハッシュを差分によらず一から計算する．
+    fn compute_zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (y, row) in self.lattices.iter_row().enumerate() {
+            for (x, &lattice) in row.iter().enumerate() {
+                if let Some(owned_geister) = lattice {
+                    hash ^= zobrist::geister_key(TableIndex::new(x, y), owned_geister);
+                }
+            }
+        }
+        if let Some(actor) = self.actor_of_cleared_geister {
+            hash ^= zobrist::cleared_key(actor);
+        }
+        if self.current_actor() == Actor::Second {
+            hash ^= zobrist::second_to_move_key();
+        }
+        hash
+    }
+}
+
+impl PartialEq for GeisterState {
+    fn eq(&self, other: &Self) -> bool {
+        // ハッシュが異なれば局面も異なるので，先に比較する
+        self.zobrist_hash == other.zobrist_hash
+            && self.rules == other.rules
+            && self.current_actor() == other.current_actor()
+            && self.actor_of_cleared_geister == other.actor_of_cleared_geister
+            && self.killed_geister_counts == other.killed_geister_counts
+            && self
+                .lattices
+                .iter_row()
+                .zip(other.lattices.iter_row())
+                .all(|(row, other_row)| row.iter().eq(other_row.iter()))
+    }
+}
+
+impl Eq for GeisterState {}
+
+impl Hash for GeisterState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 等しい状態のZobristハッシュは必ず等しい
+        state.write_u64(self.zobrist_hash);
+    }
+}
+
+impl Debug for GeisterState {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let lattices = self
+            .lattices
+            .iter_row()
+            .map(|row| row.to_vec())
+            .collect::<Vec<_>>();
+        f.debug_struct("GeisterState")
+            .field("rules", &self.rules)
+            .field("lattices", &lattices)
+            .field("killed_geister_counts", &self.killed_geister_counts)
+            .field("actor_of_cleared_geister", &self.actor_of_cleared_geister)
+            .field("ply", &self.ply)
+            .field("zobrist_hash", &self.zobrist_hash)
+            .finish()
+    }
 }
 
 impl State for GeisterState {}
@@ -103,3 +287,101 @@ impl State for GeisterState {}
 pub fn geisters() -> [Geister; 2] {
     [Geister::Evil, Geister::Holy]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeisterAction, GeisterMovement, GeisterRule, InitialPlacement, RandomPlacement};
+    use data_structure::{Pair, TableSize};
+    use minimax_strategy::Rule;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    /// 指定した状態と同じ要素から，差分更新によらずにZobristハッシュを計算する．
+    fn rebuilt_zobrist_hash(state: &GeisterState) -> u64 {
+        GeisterState::from_parts(
+            state.rules,
+            state.lattices.clone(),
+            state.killed_geister_counts.clone(),
+            state.actor_of_cleared_geister,
+            state.ply,
+        )
+        .zobrist_hash()
+    }
+
+    #[test]
+    fn incremental_zobrist_hash_matches_rebuilt_hash() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let rules_list = vec![
+            GeisterRules::standard(),
+            GeisterRules::with_field_size(TableSize::new(4, 4)).unwrap(),
+        ];
+        let mut capture_count = 0;
+        let mut escape_count = 0;
+        for rules in rules_list {
+            let rules = rules.with_max_ply(Some(200));
+            for _ in 0..50 {
+                let first_setup = RandomPlacement.select_setup(&rules, Actor::First, &mut rng);
+                let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, &mut rng);
+                let mut state =
+                    GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+                assert_eq!(rebuilt_zobrist_hash(&state), state.zobrist_hash());
+                while !GeisterRule::is_game_over(&state) {
+                    let actions =
+                        GeisterRule::iterate_available_actions(&state, state.current_actor());
+                    let action = match actions.choose(&mut rng) {
+                        Some(&action) => action,
+                        None => break,
+                    };
+                    let undo = GeisterRule::apply(&mut state, &action);
+                    assert_eq!(rebuilt_zobrist_hash(&state), state.zobrist_hash());
+                    if undo.killed_geister().is_some() {
+                        capture_count += 1;
+                    }
+                    if state.actor_of_cleared_geister().is_some() {
+                        escape_count += 1;
+                    }
+                }
+            }
+        }
+        // 取る手と上がる手の両方でハッシュを確かめたことを保証する
+        assert!(capture_count > 0);
+        assert!(escape_count > 0);
+    }
+
+    #[test]
+    fn same_position_at_different_ply_is_equal() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let rules = GeisterRules::standard();
+        let first_setup = RandomPlacement.select_setup(&rules, Actor::First, &mut rng);
+        let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, &mut rng);
+        let initial_state =
+            GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+
+        // 両者が👻を1マス進めてから元に戻すと，手数だけが異なる同じ局面になる
+        let mut state = initial_state.clone();
+        let mut moved_positions = vec![];
+        for &actor in actors().iter() {
+            let forward = match actor {
+                Actor::First => Pair::new(0, -1),
+                Actor::Second => Pair::new(0, 1),
+            };
+            let action = GeisterRule::iterate_available_actions(&state, actor)
+                .into_iter()
+                .find(|action| action.geister_movement == GeisterMovement::Direction(forward))
+                .unwrap();
+            GeisterRule::apply(&mut state, &action);
+            moved_positions.push((action.position_after_movement().unwrap(), forward, actor));
+        }
+        assert_ne!(initial_state, state);
+        for (position, forward, actor) in moved_positions {
+            let backward = GeisterMovement::Direction(Pair::new(-forward.x, -forward.y));
+            GeisterRule::apply(&mut state, &GeisterAction::new(position, backward, actor));
+        }
+
+        assert_ne!(initial_state.ply(), state.ply());
+        assert_eq!(initial_state, state);
+        assert_eq!(initial_state.zobrist_hash(), state.zobrist_hash());
+    }
+}
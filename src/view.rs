@@ -0,0 +1,250 @@
+use crate::{
+    geisters, Geister, GeisterAction, GeisterRule, GeisterRules, GeisterState, IllegalAction,
+    OwnedGeister,
+};
+use data_structure::{Table, TableIndex};
+use minimax_strategy::{actors, Actor, Rule, State};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+use std::iter;
+
+/// あるプレイヤーから観測できる👻．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObservedGeister {
+    /// 👻の所有者．
+    pub owner: Actor,
+    /// 👻の種類．観測者にとって種類が分からない👻 (相手の👻) は`None`となる．
+    pub geister: Option<Geister>,
+}
+
+/// 指定したプレイヤーが知りうる情報だけを含むゲームGeisterの状態．
+/// 自分の👻の種類，相手の👻の位置，取り除かれた👻の種類ごとの数を知ることができる．
+#[derive(Clone)]
+pub struct PlayerView {
+    /// このゲームのルール．
+    rules: GeisterRules,
+    /// 観測者．
+    viewpoint: Actor,
+    /// フィールドに存在する👻．
+    pub lattices: Table<Option<ObservedGeister>>,
+    /// フィールドから取り除かれた👻の数．
+    /// 取り除かれた👻の種類は両プレイヤーに公開される．
+    killed_geister_counts: HashMap<OwnedGeister, usize>,
+    /// フィールドから上がった👻の所有者．
+    pub actor_of_cleared_geister: Option<Actor>,
+    /// ゲーム開始から進んだ手数．
+    ply: usize,
+}
+
+impl PlayerView {
+    /// 指定した状態を，指定したプレイヤーから観測した結果を返す．
+    pub fn new(state: &GeisterState, viewpoint: Actor) -> Self {
+        let size = state.rules().field_size();
+        let mut lattices = Table::from_fill(None, size);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let position = TableIndex::new(x, y);
+                lattices[position] = state.geister_at(position).map(|owned_geister| {
+                    // 相手の👻の種類は隠す
+                    let geister = if owned_geister.owner == viewpoint {
+                        Some(owned_geister.geister)
+                    } else {
+                        None
+                    };
+                    ObservedGeister {
+                        owner: owned_geister.owner,
+                        geister,
+                    }
+                });
+            }
+        }
+
+        let mut killed_geister_counts = HashMap::new();
+        for &geister in geisters().iter() {
+            for &actor in actors().iter() {
+                let owned_geister = OwnedGeister::new(geister, actor);
+                killed_geister_counts
+                    .insert(owned_geister, state.killed_geister_count(owned_geister));
+            }
+        }
+
+        Self {
+            rules: *state.rules(),
+            viewpoint,
+            lattices,
+            killed_geister_counts,
+            actor_of_cleared_geister: state.actor_of_cleared_geister(),
+            ply: state.ply(),
+        }
+    }
+
+    /// 観測結果を構成する要素から観測結果を生成する．
+    /// 各要素の整合性は呼び出し側が保証する必要がある．
+    pub fn from_parts(
+        rules: GeisterRules,
+        viewpoint: Actor,
+        lattices: Table<Option<ObservedGeister>>,
+        killed_geister_counts: HashMap<OwnedGeister, usize>,
+        actor_of_cleared_geister: Option<Actor>,
+        ply: usize,
+    ) -> Self {
+        Self {
+            rules,
+            viewpoint,
+            lattices,
+            killed_geister_counts,
+            actor_of_cleared_geister,
+            ply,
+        }
+    }
+
+    /// このゲームのルールを返す．
+    pub fn rules(&self) -> &GeisterRules {
+        &self.rules
+    }
+
+    /// 観測者を返す．
+    pub fn viewpoint(&self) -> Actor {
+        self.viewpoint
+    }
+
+    /// ゲーム開始から進んだ手数を返す．
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// 指定した👻が何体フィールドから取り除かれたか返す．
+    pub fn killed_geister_count(&self, owned_geister: OwnedGeister) -> usize {
+        self.killed_geister_counts[&owned_geister]
+    }
+
+    /// 観測者がとれる行動を列挙する．
+    pub fn available_actions(&self) -> Vec<GeisterAction> {
+        // 観測者の行動の可否は相手の👻の種類に依存しないため，種類は適当に決めてよい
+        let state = self.to_state_with(self.unknown_geister_candidates());
+        GeisterRule::iterate_available_actions(&state, self.viewpoint)
+    }
+
+    /// 観測者が指定した行動をとれるか確かめる．
+    /// とれない場合はその理由を返す．
+    pub fn validate(&self, action: &GeisterAction) -> Result<(), IllegalAction> {
+        // 行動の可否は相手の👻の種類に依存しないため，種類は適当に決めてよい
+        let state = self.to_state_with(self.unknown_geister_candidates());
+        GeisterRule::validate(&state, action)
+    }
+
+    /// 種類の分からない👻に，観測と矛盾しない種類をランダムに割り当てた状態を返す．
+    pub fn determinize<R: Rng + ?Sized>(&self, rng: &mut R) -> GeisterState {
+        let mut candidates = self.unknown_geister_candidates();
+        candidates.shuffle(rng);
+        self.to_state_with(candidates)
+    }
+
+    /// 相手の👻のうち，フィールドに残っているはずの👻の種類を列挙する．
+    fn unknown_geister_candidates(&self) -> Vec<Geister> {
+        let opponent = self.viewpoint.opponent();
+        geisters()
+            .iter()
+            .flat_map(|&geister| {
+                let remaining_count = self.rules.initial_geister_count(geister)
+                    - self.killed_geister_count(OwnedGeister::new(geister, opponent));
+                iter::repeat_n(geister, remaining_count)
+            })
+            .collect()
+    }
+
+    /// 種類の分からない👻に，フィールドの左上から順に指定した種類を割り当てた状態を返す．
+    fn to_state_with(&self, candidates: Vec<Geister>) -> GeisterState {
+        // 👻が上がった後などで候補が足りない場合は，邪悪な👻とみなす
+        let mut candidates = candidates.into_iter().chain(iter::repeat(Geister::Evil));
+
+        let size = self.rules.field_size();
+        let mut lattices = Table::from_fill(None, size);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let position = TableIndex::new(x, y);
+                lattices[position] = self.lattices[position].map(|observed_geister| {
+                    let geister = observed_geister
+                        .geister
+                        .unwrap_or_else(|| candidates.next().expect("Candidates never run out"));
+                    OwnedGeister::new(geister, observed_geister.owner)
+                });
+            }
+        }
+
+        GeisterState::from_parts(
+            self.rules,
+            lattices,
+            self.killed_geister_counts.clone(),
+            self.actor_of_cleared_geister,
+            self.ply,
+        )
+    }
+}
+
+impl State for PlayerView {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InitialPlacement, RandomPlacement};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// 指定したプレイヤーの指定した種類の👻が，フィールドに何体いるか返す．
+    fn count_on_field(state: &GeisterState, owned_geister: OwnedGeister) -> usize {
+        let size = state.rules().field_size();
+        (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| TableIndex::new(x, y)))
+            .filter(|&position| state.geister_at(position) == Some(owned_geister))
+            .count()
+    }
+
+    #[test]
+    fn determinize_keeps_visible_geisters_and_hidden_counts() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let rules = GeisterRules::standard();
+        let size = rules.field_size();
+        for _ in 0..10 {
+            let first_setup = RandomPlacement.select_setup(&rules, Actor::First, &mut rng);
+            let second_setup = RandomPlacement.select_setup(&rules, Actor::Second, &mut rng);
+            let mut state =
+                GeisterState::create_initial_state(rules, &first_setup, &second_setup).unwrap();
+            while !GeisterRule::is_game_over(&state) {
+                for &viewpoint in actors().iter() {
+                    let view = PlayerView::new(&state, viewpoint);
+                    let determinized = view.determinize(&mut rng);
+                    assert_eq!(state.ply(), determinized.ply());
+                    for y in 0..size.y {
+                        for x in 0..size.x {
+                            let position = TableIndex::new(x, y);
+                            let actual = state.geister_at(position);
+                            let guessed = determinized.geister_at(position);
+                            // 👻の有無と所有者は変わらず，観測者の👻は種類も変わらない
+                            assert_eq!(actual.map(|g| g.owner), guessed.map(|g| g.owner));
+                            if actual.is_some_and(|g| g.owner == viewpoint) {
+                                assert_eq!(actual, guessed);
+                            }
+                        }
+                    }
+                    for &actor in actors().iter() {
+                        for &geister in geisters().iter() {
+                            let owned_geister = OwnedGeister::new(geister, actor);
+                            assert_eq!(
+                                state.killed_geister_count(owned_geister),
+                                determinized.killed_geister_count(owned_geister)
+                            );
+                            assert_eq!(
+                                count_on_field(&state, owned_geister),
+                                count_on_field(&determinized, owned_geister)
+                            );
+                        }
+                    }
+                }
+                let actions = GeisterRule::iterate_available_actions(&state, state.current_actor());
+                GeisterRule::apply(&mut state, actions.choose(&mut rng).unwrap());
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{Geister, OwnedGeister};
+use data_structure::TableIndex;
+use minimax_strategy::Actor;
+
+/// 👻の種類・所有者・位置に対応する乱数の種．
+const GEISTER_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+/// フィールドから上がった👻の所有者に対応する乱数の種．
+const CLEARED_SEED: u64 = 0xD1B5_4A32_D192_ED03;
+/// 後手の手番であることに対応する乱数の種．
+const SECOND_TO_MOVE_SEED: u64 = 0x8CB9_2BA7_2F3D_8DD7;
+
+/// 指定した位置に指定した👻がいることに対応する，Zobristハッシュ用の乱数を返す．
+/// 乱数は表として保持せず要素から計算するため，フィールドの大きさによらず同じ要素には同じ乱数が対応する．
+pub fn geister_key(position: TableIndex, owned_geister: OwnedGeister) -> u64 {
+    let owner = match owned_geister.owner {
+        Actor::First => 0,
+        Actor::Second => 1,
+    };
+    let geister = match owned_geister.geister {
+        Geister::Holy => 0,
+        Geister::Evil => 1,
+    };
+    let element = (position.y as u64) << 34 | (position.x as u64) << 2 | owner << 1 | geister;
+    mix(GEISTER_SEED.wrapping_add(element))
+}
+
+/// 指定したプレイヤーの👻がフィールドから上がったことに対応する乱数を返す．
+pub fn cleared_key(actor: Actor) -> u64 {
+    let element = match actor {
+        Actor::First => 0,
+        Actor::Second => 1,
+    };
+    mix(CLEARED_SEED.wrapping_add(element))
+}
+
+/// 後手の手番であることに対応する乱数を返す．
+pub fn second_to_move_key() -> u64 {
+    mix(SECOND_TO_MOVE_SEED)
+}
+
+/// 入力のビットをよく混ぜ合わせた値を返す (SplitMix64の出力関数)．
+fn mix(z: u64) -> u64 {
+    let z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}